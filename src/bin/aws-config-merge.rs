@@ -0,0 +1,70 @@
+//! A git merge driver for AWS config and credentials files, built on [AwsConfigFile::merge]. Unlike the default
+//! line-based merge, it merges the files setting by setting, so changes to different settings never conflict
+//! and the comments of both sides are kept.
+//!
+//! To use it, register the driver in your git config:
+//!
+//! ```text
+//! [merge "aws-config"]
+//!     name = AWS config merge
+//!     driver = aws-config-merge %O %A %B
+//! ```
+//!
+//! and assign it to the files in `.gitattributes`:
+//!
+//! ```text
+//! config merge=aws-config
+//! credentials merge=aws-config
+//! ```
+//!
+//! The merged file is written over `%A`. The exit code is 0 for a clean merge and 1 if there are conflicts, in
+//! which case the merged file keeps our side of each of them and the conflicts are listed on stderr. Any other
+//! failure, such as a file which can't be parsed, exits with 2 and leaves `%A` untouched.
+
+use aws_config_mod::AwsConfigFile;
+use std::{fs, process::ExitCode};
+
+/// The usage message, printed when the arguments are wrong
+const USAGE: &str = "usage: aws-config-merge <base> <ours> <theirs>";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [base, ours, theirs] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    match run(base, ours, theirs) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("aws-config-merge: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Merge the three files and write the result over `ours`. Returns whether the merge was clean.
+fn run(base: &str, ours: &str, theirs: &str) -> Result<bool, String> {
+    let result = AwsConfigFile::merge(&read(base)?, &read(ours)?, &read(theirs)?);
+
+    result
+        .conflicts()
+        .iter()
+        .for_each(|conflict| eprintln!("{conflict}"));
+
+    fs::write(ours, result.merged().to_string())
+        .map_err(|error| format!("failed to write {ours}: {error}"))?;
+
+    Ok(result.is_clean())
+}
+
+/// Read and parse one of the files given to the driver
+fn read(path: &str) -> Result<AwsConfigFile, String> {
+    let content =
+        fs::read_to_string(path).map_err(|error| format!("failed to read {path}: {error}"))?;
+
+    content
+        .parse()
+        .map_err(|error| format!("failed to parse {path}: {error}"))
+}
@@ -1,18 +1,325 @@
 //! A custom error type to handle various kinds of parsing errors
 
-use crate::SectionType;
+use crate::{ConfigPath, SectionName, SectionType, SettingName};
+use nom::error::{VerboseError, VerboseErrorKind};
+use std::fmt::Display;
 use thiserror::Error;
 
-/// Custom error type. Currently incomplete but will eventually feature better
-/// parse-error diagnostics
+/// Custom error type for everything that can go wrong while parsing or modifying a configuration.
 #[derive(Debug, Error)]
 pub enum Error {
     /// Indicates a failure to parse either a configuration file or a path to a setting or section
-    #[error("Failed to parse config file:\n\t{0}")]
-    ParseError(#[from] nom::Err<nom::error::VerboseError<String>>),
+    #[error("Failed to parse input: {0}")]
+    ParseError(#[from] ParseError),
 
     /// Represents the failure that occurs when trying to create a [crate::SectionPath] for a [crate::SectionType]
     /// which requires a [crate::SectionName]
     #[error("A section name is required for section type '{0}'")]
     SectionNameNeeded(SectionType),
+
+    /// Indicates an attempt to nest a setting under a [crate::Setting] which holds a single value. Such a setting
+    /// has to be converted explicitly with [crate::AwsConfigFile::convert_to_nested] first.
+    #[error("The setting '{0}' has a single value, so it cannot hold nested settings")]
+    NotNested(SettingName),
+
+    /// Indicates an attempt to give a value to a [ConfigPath] which points to a [crate::Section]
+    #[error("The path '{0}' points to a section, which cannot hold a value")]
+    NotASetting(ConfigPath),
+
+    /// Indicates an attempt to rename a [crate::Section] which has no name, such as `[plugins]`
+    #[error("The section '{0}' has no name, so it cannot be renamed")]
+    Unnamed(ConfigPath),
+
+    /// Indicates that the item a [ConfigPath] points to doesn't exist, when it has to
+    #[error("Nothing exists at the path '{0}'")]
+    NotFound(ConfigPath),
+
+    /// Indicates an attempt to give an item the [ConfigPath] of another item which already exists
+    #[error("Something already exists at the path '{0}'")]
+    AlreadyExists(ConfigPath),
+
+    /// Indicates an attempt to remove a section which profiles still refer to, such as an `[sso-session]` passed
+    /// to [crate::AwsConfigFile::remove_sso_session] with [crate::Dependents::Refuse]
+    #[error("The section '{section_path}' is still referred to by the profiles {}", quoted_names(.profiles))]
+    StillReferenced {
+        /// The path of the section which was not removed
+        section_path: ConfigPath,
+        /// The names of the profiles which refer to it
+        profiles: Vec<SectionName>,
+    },
+
+    /// Indicates that one of the edits passed to [crate::AwsConfigFile::apply] failed, in which case none of
+    /// them were applied
+    #[error("Edit number {index} failed: {source}")]
+    EditFailed {
+        /// The position of the failed [crate::Edit] in the list, starting at zero
+        index: usize,
+
+        /// The reason it failed
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// Indicates that a value does not have the form required by a typed setting, such as a [crate::Region]
+    #[error("Invalid value '{value}', expected {expected}")]
+    InvalidValue {
+        /// The value which was rejected
+        value: String,
+
+        /// A description of the values which are accepted
+        expected: &'static str,
+    },
+
+    /// Indicates that a setting read through a typed view, such as a [crate::Profile], holds an invalid value
+    #[error("Invalid value for the setting '{setting_name}': {source}")]
+    InvalidSetting {
+        /// The name of the setting
+        setting_name: SettingName,
+
+        /// The reason the value is invalid
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// Indicates that a [crate::Section] could not be converted to or from a user-defined type with serde,
+    /// such as a value which isn't a valid number for a numeric field
+    #[error("Failed to convert a section: {0}")]
+    Serde(String),
+}
+
+impl Error {
+    /// Describe the invalid value of a setting read through a typed view, such as a [crate::Profile]
+    pub(crate) fn invalid_setting(setting_name: &str, source: Error) -> Self {
+        Self::InvalidSetting {
+            setting_name: SettingName(setting_name.to_string()),
+            source: Box::new(source),
+        }
+    }
+}
+
+/// List the names as `'A', 'B'` for an error message
+fn quoted_names(names: &[SectionName]) -> String {
+    names
+        .iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The syntactic construct that the parser was looking for when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Expected {
+    /// A section header such as `[profile A]`
+    Header,
+
+    /// A section type such as `profile` or `sso-session`
+    SectionType,
+
+    /// A section name, such as 'A' in `[profile A]`
+    SectionName,
+
+    /// The name of a setting, in other words the part before the '='
+    SettingName,
+
+    /// The '=' sign between a setting name and its value
+    Equal,
+
+    /// The value of a setting
+    Value,
+
+    /// An indented setting belonging to a nested block
+    NestedSetting,
+
+    /// The '.' which separates the segments of a path
+    PathSeparator,
+
+    /// The end of the input, meaning that there was unexpected content left over
+    EndOfInput,
+}
+
+impl Expected {
+    /// Every variant, used to map a nom context label back to its [Expected]
+    const ALL: [Self; 9] = [
+        Self::Header,
+        Self::SectionType,
+        Self::SectionName,
+        Self::SettingName,
+        Self::Equal,
+        Self::Value,
+        Self::NestedSetting,
+        Self::PathSeparator,
+        Self::EndOfInput,
+    ];
+
+    /// The label used when attaching this construct as a [nom::error::context] to a parser
+    pub(crate) const fn label(&self) -> &'static str {
+        match self {
+            Self::Header => "a section header",
+            Self::SectionType => "a section type",
+            Self::SectionName => "a section name",
+            Self::SettingName => "a setting name",
+            Self::Equal => "'='",
+            Self::Value => "a value",
+            Self::NestedSetting => "an indented nested setting",
+            Self::PathSeparator => "'.'",
+            Self::EndOfInput => "the end of the input",
+        }
+    }
+
+    /// Find the [Expected] that corresponds to a context label
+    fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|expected| expected.label() == label)
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// A parse failure with its position in the input and a rendered snippet of the offending line.
+///
+/// ```text
+/// expected '=' at line 3, column 8
+///   |
+/// 3 | region us-east-1
+///   |        ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The byte offset into the input at which parsing failed
+    pub(crate) offset: usize,
+
+    /// The one-based line on which parsing failed
+    pub(crate) line: usize,
+
+    /// The one-based column, counted in characters, at which parsing failed
+    pub(crate) column: usize,
+
+    /// What the parser was looking for at the failure position
+    pub(crate) expected: Expected,
+
+    /// The offending line of input, followed by a line with a caret under the failure position
+    pub(crate) snippet: String,
+}
+
+impl ParseError {
+    /// Build a [ParseError] from the error returned by a parser which was given `source` as input.
+    pub(crate) fn new(source: &str, error: nom::Err<VerboseError<&str>>) -> Self {
+        let errors = match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => error.errors,
+            nom::Err::Incomplete(_) => vec![],
+        };
+
+        let remaining = errors.first().map(|(input, _)| *input).unwrap_or_default();
+
+        let expected = errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(label) => Expected::from_label(label),
+                _ => None,
+            })
+            .unwrap_or(Expected::EndOfInput);
+
+        Self::at(source, source.len() - remaining.len(), expected)
+    }
+
+    /// Build a [ParseError] for the given byte offset into `source`.
+    pub(crate) fn at(source: &str, offset: usize, expected: Expected) -> Self {
+        let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = source[offset..]
+            .find(['\r', '\n'])
+            .map(|i| offset + i)
+            .unwrap_or(source.len());
+
+        let line = source[..offset].matches('\n').count() + 1;
+        let prefix = &source[line_start..offset];
+        let column = prefix.chars().count() + 1;
+
+        let gutter = " ".repeat(line.to_string().len());
+        let caret_padding = prefix
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let snippet = format!(
+            "{gutter} |\n{line} | {}\n{gutter} | {caret_padding}^",
+            &source[line_start..line_end]
+        );
+
+        Self {
+            offset,
+            line,
+            column,
+            expected,
+            snippet,
+        }
+    }
+
+    /// The byte offset into the input at which parsing failed
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The one-based line on which parsing failed
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The one-based column, counted in characters, at which parsing failed
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// What the parser was looking for when it failed
+    pub fn expected(&self) -> Expected {
+        self.expected
+    }
+
+    /// The offending line with a caret pointing at the failure position
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} at line {}, column {}\n{}",
+            self.expected, self.line, self.column, self.snippet
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::{Expected, ParseError};
+
+    #[test]
+    fn renders_a_snippet_with_a_caret() {
+        let source = "[default]\nregion us-east-1\n";
+        let error = ParseError::at(source, 17, Expected::Equal);
+
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 8);
+        assert_eq!(error.snippet(), "  |\n2 | region us-east-1\n  |        ^");
+        assert_eq!(
+            error.to_string(),
+            "expected '=' at line 2, column 8\n  |\n2 | region us-east-1\n  |        ^"
+        )
+    }
+
+    #[test]
+    fn points_past_the_end_of_the_input() {
+        let error = ParseError::at("profile.", 8, Expected::SectionName);
+
+        assert_eq!(error.line(), 1);
+        assert_eq!(error.column(), 9);
+        assert_eq!(error.snippet(), "  |\n1 | profile.\n  |         ^");
+    }
 }
@@ -1,6 +1,14 @@
 //! Traits, functions, etc to help with parsing config files.
 
-use nom::{bytes::complete::tag, error::VerboseError, IResult};
+use crate::error::{Expected, ParseError};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::eof,
+    error::{context, VerboseError},
+    sequence::terminated,
+    IResult,
+};
 
 /// The return type from a parser
 pub type ParserOutput<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
@@ -19,9 +27,9 @@ pub(crate) fn hash(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
     tag("#")(input)
 }
 
-/// Matches a single newline character
+/// Matches a single line ending, either `\n` or `\r\n`
 pub(crate) fn newline(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
-    tag("\n")(input)
+    alt((tag("\n"), tag("\r\n")))(input)
 }
 
 /// Matches a single newline character
@@ -29,15 +37,13 @@ pub(crate) fn equal(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
     tag("=")(input)
 }
 
-/// A helper to convert [nom::Err<VerboseError<&str>>] to an owned type
-pub(crate) fn to_owned_input(
-    error: nom::Err<VerboseError<&str>>,
-) -> nom::Err<VerboseError<String>> {
-    error.map(|error| VerboseError {
-        errors: error
-            .errors
-            .into_iter()
-            .map(|(a, b)| (a.to_string(), b))
-            .collect::<Vec<_>>(),
-    })
+/// Runs the parser for `P` over the whole of `input`, converting any failure, including leftover input,
+/// into a [ParseError] that points at the offending position.
+pub(crate) fn parse_complete<'a, P>(input: &'a str) -> Result<P::Output, ParseError>
+where
+    P: Parsable<'a>,
+{
+    terminated(P::parse, context(Expected::EndOfInput.label(), eof))(input)
+        .map(|(_, output)| output)
+        .map_err(|error| ParseError::new(input, error))
 }
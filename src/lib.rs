@@ -20,6 +20,8 @@
 //! ## Usage
 //!
 //! ```
+//! use aws_config_mod::{AwsConfigFile, SettingPath, Value};
+//!
 //! // Assuming you already read the configuration file to a string
 //! let config_content = r#"
 //! [profile A]
@@ -35,29 +37,75 @@
 //! ec2 =
 //!   endpoint_url = https://profile-b-ec2-endpoint.aws"#;
 //!
-//! let mut config = AwsConfigFile::parse(SAMPLE_FILE).expect("Sample file should be valid");
+//! let mut config: AwsConfigFile = config_content.parse().expect("Sample file should be valid");
 //!
 //! let setting_path = SettingPath::try_from("profile.A.credential_source").expect("Should parse");
-//! config.set(setting_path, Value::from("my-new-credential-source"));
+//! config
+//!     .set(setting_path, Value::from("my-new-credential-source"))
+//!     .expect("Should be a setting");
 //! let stringified = config.to_string();
 //! // Write the content back to your file
 //! ```
 //!
+//! ## Typed profiles
+//!
+//! The standard settings of a profile, such as `region`, `role_arn` and `duration_seconds`, can be read and written
+//! as typed values through a [Profile] view from [AwsConfigFile::profile] and [AwsConfigFile::profile_mut]. Reading
+//! a setting whose value doesn't have the required form fails with [Error::InvalidSetting].
+//!
+//! `[sso-session]` sections have a similar [SsoSession] view, which treats `sso_registration_scopes` as a
+//! list. An SSO session can be removed with [AwsConfigFile::remove_sso_session], which either refuses while
+//! profiles still use it or removes those profiles as well.
+//!
+//! `[services]` sections have a [ServicesSection] view, which maps each service to its settings, such as
+//! its `endpoint_url`. Service identifiers are normalized like the SDKs do, so `Elastic Beanstalk` is found as
+//! `elastic_beanstalk`.
+//!
+//! ## Features
+//!
+//! - `serde`: serialization of [Edit]s and [ConfigDiff]s, so that a list of edits can be sent to
+//!   [AwsConfigFile::apply] as JSON, and a diff can be rendered as JSON. Config and credentials files
+//!   are serialized as plain maps from section type to name to setting to value, without comments. A
+//!   file deserialized from such a map can be applied to a parsed file with [AwsConfigFile::update_from],
+//!   which keeps the comments of the parsed file.
+//!   A single section can also be read into a user-defined type with [Section::deserialize], and written back
+//!   with [Section::update_from], which only changes the settings whose values differ.
+//!
+//! ## Merge driver
+//!
+//! The crate comes with an `aws-config-merge` binary, which merges config and credentials files setting by
+//! setting with [AwsConfigFile::merge] and can be used as a git merge driver. Register it in your git config
+//! and assign it to the files in `.gitattributes`:
+//!
+//! ```text
+//! # .git/config
+//! [merge "aws-config"]
+//!     name = AWS config merge
+//!     driver = aws-config-merge %O %A %B
+//!
+//! # .gitattributes
+//! config merge=aws-config
+//! credentials merge=aws-config
+//! ```
+//!
+//! It exits with 1 and lists the conflicts on stderr if both sides changed the same setting differently.
+//!
 //! ## TODOs
 //!
-//! - improved error messages
 //! - automatic config file loading via standard aws config locations and environment variables
-//! - detect and match formatting
-//! - set formatting
 //! - utilize aws types
-//! - add more strongly typed structs for various aspects of the configuration
 
 mod error;
 mod lexer;
 mod model;
 
-pub use error::Error;
+pub use error::{Error, Expected, ParseError};
 pub use model::{
-    AwsConfigFile, AwsCredentialsFile, NestedSetting, NestedSettingPath, Section, SectionName,
-    SectionPath, SectionType, Setting, SettingName, SettingPath, Value, ValueType,
+    AccountId, Arn, AwsConfigFile, AwsCredentialsFile, Change, ConfigDiff, ConfigHeader,
+    ConfigItem, ConfigItemMut, ConfigItemRef, ConfigPath, CredentialHeader, CredentialSource,
+    Dependents, Difference, Edit, EndpointUrl, FormatOptions, Header, LineEnding, MergeConflict,
+    MergeResult, NestedSetting, NestedSettingPath, OccupiedSectionEntry, OccupiedSettingEntry,
+    Output, Profile, Region, Report, Section, SectionEntry, SectionName, SectionPath, SectionType,
+    ServiceId, ServiceSettings, ServicesSection, Setting, SettingEntry, SettingName, SettingPath,
+    SsoSession, VacantSectionEntry, VacantSettingEntry, Value, ValueType,
 };
@@ -1,32 +1,84 @@
 //! Collection of structs that represent the various structures of an aws config file.
 
+mod account_id;
+mod arn;
 mod config_file;
+mod config_item;
+mod config_path;
+mod credential_source;
 mod credentials_file;
+mod dependents;
+mod diff;
+mod edit;
+mod endpoint_url;
+mod entry;
 mod equal;
+mod format_options;
 mod header;
 mod indent;
+mod line_ending;
+mod merge;
 mod nested_setting;
 mod nested_settings;
+mod output;
+mod profile;
+mod raw_line;
+mod region;
 mod section;
 mod section_name;
 mod section_path;
 mod section_type;
+#[cfg(feature = "serde")]
+mod serde_map;
+#[cfg(feature = "serde")]
+mod serde_section;
+#[cfg(feature = "serde")]
+mod serde_string;
+mod service_id;
+mod service_settings;
+mod services_section;
 mod setting;
 mod setting_name;
 mod setting_path;
+mod sso_session;
+mod style;
 mod value;
 mod value_type;
 mod whitespace;
 
+pub use account_id::AccountId;
+pub use arn::Arn;
 pub use config_file::AwsConfigFile;
+pub use config_item::{ConfigItem, ConfigItemMut, ConfigItemRef};
+pub use config_path::ConfigPath;
+pub use credential_source::CredentialSource;
 pub use credentials_file::AwsCredentialsFile;
+pub use dependents::Dependents;
+pub use diff::{ConfigDiff, Difference};
+pub use edit::{Change, Edit, Report};
+pub use endpoint_url::EndpointUrl;
+pub use entry::{
+    OccupiedSectionEntry, OccupiedSettingEntry, SectionEntry, SettingEntry, VacantSectionEntry,
+    VacantSettingEntry,
+};
+pub use format_options::FormatOptions;
+pub use header::{ConfigHeader, CredentialHeader, Header};
+pub use line_ending::LineEnding;
+pub use merge::{MergeConflict, MergeResult};
 pub use nested_setting::NestedSetting;
+pub use output::Output;
+pub use profile::Profile;
+pub use region::Region;
 pub use section::Section;
 pub use section_name::SectionName;
 pub use section_path::SectionPath;
 pub use section_type::SectionType;
+pub use service_id::ServiceId;
+pub use service_settings::ServiceSettings;
+pub use services_section::ServicesSection;
 pub use setting::Setting;
 pub use setting_name::SettingName;
 pub use setting_path::{NestedSettingPath, SettingPath};
+pub use sso_session::SsoSession;
 pub use value::Value;
 pub use value_type::ValueType;
@@ -0,0 +1,36 @@
+//! Contains the [AccountId] type, which is the typed value of the `sso_account_id` setting.
+
+use crate::Error;
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+/// The ID of an AWS account, which is made of exactly twelve digits, such as `123456789012`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccountId(String);
+
+impl FromStr for AccountId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.len() == 12 && s.chars().all(|c| c.is_ascii_digit()) {
+            true => Ok(Self(s.to_string())),
+            false => Err(Error::InvalidValue {
+                value: s.to_string(),
+                expected: "an account ID of twelve digits",
+            }),
+        }
+    }
+}
+
+impl Display for AccountId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for AccountId {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,109 @@
+//! Contains the [Arn] type, which is the typed value of settings such as `role_arn`.
+
+use crate::Error;
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+/// An Amazon Resource Name, such as `arn:aws:iam::123456789012:role/admin`. It is made of six parts separated
+/// by ':', of which the partition, the service and the resource can't be empty. The resource may contain ':'
+/// itself.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Arn(String);
+
+impl Arn {
+    /// The part of the ARN at the given position, which is known to be there
+    fn part(&self, index: usize) -> &str {
+        self.0.splitn(6, ':').nth(index).unwrap_or_default()
+    }
+
+    /// The partition, such as `aws` or `aws-cn`
+    pub fn partition(&self) -> &str {
+        self.part(1)
+    }
+
+    /// The service, such as `iam`
+    pub fn service(&self) -> &str {
+        self.part(2)
+    }
+
+    /// The region, which is empty for global services such as IAM
+    pub fn region(&self) -> &str {
+        self.part(3)
+    }
+
+    /// The account ID, which is empty for some resources such as S3 buckets
+    pub fn account_id(&self) -> &str {
+        self.part(4)
+    }
+
+    /// The resource, such as `role/admin`
+    pub fn resource(&self) -> &str {
+        self.part(5)
+    }
+}
+
+impl FromStr for Arn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.splitn(6, ':').collect::<Vec<_>>();
+        let is_valid = matches!(
+            parts.as_slice(),
+            ["arn", partition, service, _, _, resource]
+                if !partition.is_empty() && !service.is_empty() && !resource.is_empty()
+        ) && !s.contains(char::is_whitespace);
+
+        match is_valid {
+            true => Ok(Self(s.to_string())),
+            false => Err(Error::InvalidValue {
+                value: s.to_string(),
+                expected: "an ARN such as 'arn:aws:iam::123456789012:role/admin'",
+            }),
+        }
+    }
+}
+
+impl Display for Arn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for Arn {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Arn;
+
+    #[test]
+    fn arns_are_split_into_their_parts() {
+        let arn = "arn:aws:iam::123456789012:role/path:with:colons"
+            .parse::<Arn>()
+            .expect("Should be valid");
+
+        assert_eq!(arn.partition(), "aws");
+        assert_eq!(arn.service(), "iam");
+        assert_eq!(arn.region(), "");
+        assert_eq!(arn.account_id(), "123456789012");
+        assert_eq!(arn.resource(), "role/path:with:colons");
+    }
+
+    #[test]
+    fn malformed_arns_are_rejected() {
+        for arn in [
+            "",
+            "arn:aws:iam::123456789012",
+            "aws:iam::123456789012:role/a",
+            "arn::iam::123456789012:role/a",
+            "arn:aws:iam::123456789012:",
+            "arn:aws:iam::123456789012:role/a b",
+        ] {
+            assert!(arn.parse::<Arn>().is_err(), "{arn}");
+        }
+    }
+}
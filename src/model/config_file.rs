@@ -1,26 +1,115 @@
 //! The main workhorse of the crate, handling parsing, manipulating, and stringifying aws configuration files.
 
 use super::{
-    header::ConfigHeader, whitespace::Whitespace, NestedSetting, NestedSettingPath, Section,
-    SectionName, SectionPath, SectionType, Setting, SettingPath, Value,
+    diff::{diff_sections, ConfigDiff},
+    edit::{Edit, Report},
+    entry::{OccupiedSectionEntry, SectionEntry, VacantSectionEntry},
+    format_options::FormatOptions,
+    header::ConfigHeader,
+    merge::{merge, merge_into, Comments, MergeResult},
+    raw_line::RawLines,
+    section::{
+        end_file_line, format_file, insert_section_at, move_section, parse_sections,
+        remove_section_at, reorder_sections,
+    },
+    style::Style,
+    whitespace::Whitespace,
+    ConfigItem, ConfigItemMut, ConfigItemRef, ConfigPath, Dependents, LineEnding, NestedSetting,
+    NestedSettingPath, Profile, Region, Section, SectionName, SectionPath, SectionType,
+    ServicesSection, Setting, SettingPath, SsoSession, Value,
 };
-use crate::lexer::{to_owned_input, Parsable};
-use nom::combinator::map;
-use nom::Parser;
-use nom::{
-    combinator::{eof, opt},
-    error::VerboseError,
-    multi::many0,
-    sequence::tuple,
-    IResult,
-};
-use std::{fmt::Display, str::FromStr};
+use crate::error::ParseError;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
 /// Represents a complete aws config file. Note that this struct is not intended for use with a
 /// credentials file although it can still successfully parse one. To handle credential files specifically,
 /// use [crate::AwsCredentialsFile].
+///
+/// # Comments and blank lines
+///
+/// Every line of the file belongs to exactly one item, which decides what happens to it when that item is removed:
+///
+/// - The rest of the line after a header or a setting, including an inline comment, belongs to that header
+///   or setting.
+/// - Comment lines directly above a [Section] header, along with the blank lines above those comments, belong
+///   to that [Section]. Any other lines between two sections belong to the [Section] before them.
+/// - Comment lines and blank lines between two settings belong to the [Setting] below them. The same goes for
+///   [NestedSetting]s within their block.
+/// - Comment lines and blank lines at the start of the file, which aren't attached to the first header,
+///   and the comment lines and blank lines at the end of the file, belong to the file itself.
+///
+/// When an item is removed, all of its lines are removed with it, with one exception: the blank lines at the
+/// very top of an item separate it from whatever is above it, so they are handed to the item that follows it.
+/// If that item is already separated by at least as many blank lines, or nothing follows, they are dropped.
+///
+/// ```
+/// # use aws_config_mod::{AwsConfigFile, SectionPath};
+/// let mut config: AwsConfigFile = "[profile A]\nregion = us-east-1\n\n# B is deprecated\n[profile B]\nregion = us-west-2\n\n[profile C]\nregion = eu-west-1\n"
+///     .parse()
+///     .unwrap();
+///
+/// let path = SectionPath::try_from("profile.B").unwrap();
+/// config.remove_section(&path);
+///
+/// assert_eq!(config.to_string(), "[profile A]\nregion = us-east-1\n\n[profile C]\nregion = eu-west-1\n");
+/// ```
+///
+/// # Reordering
+///
+/// Sections and settings can be sorted or moved, and the lines they own, as described above, move along with
+/// them. The blank lines at the top of each item are the exception: they separate whatever ends up at that
+/// position from the item above it, so they stay where they are. The comment lines at the head of the file
+/// that aren't attached to the first header also stay where they are, so a comment at the top of the file
+/// which is followed by a blank line stays at the top.
+///
+/// ```
+/// # use aws_config_mod::AwsConfigFile;
+/// let mut config: AwsConfigFile = "# managed by a tool\n\n# the fallback\n[profile B]\nregion = us-west-2\n\n# the main one\n[profile A]\nregion = us-east-1\n"
+///     .parse()
+///     .unwrap();
+///
+/// config.sort_sections_by(|first, second| first.get_name().cmp(&second.get_name()));
+///
+/// assert_eq!(
+///     config.to_string(),
+///     "# managed by a tool\n\n# the main one\n[profile A]\nregion = us-east-1\n\n# the fallback\n[profile B]\nregion = us-west-2\n"
+/// );
+/// ```
+///
+/// # The default profile
+///
+/// The default profile can be written either as `[default]` or as `[profile default]`, and both are found with
+/// the path `profile.default` or its short form `default`. Each keeps its own form when the file is written
+/// back out, and a default profile created by this crate is written as `[default]`, like the AWS CLI does.
+/// If a file contains both, `[profile default]` takes precedence and `[default]` is ignored by lookups, which
+/// is how the AWS SDKs resolve the conflict.
+///
+/// # Formatting
+///
+/// Settings and sections added to a parsed file are formatted like the ones already there. The spacing
+/// around the '=' sign, the indentation of nested settings, the number of blank lines between sections and the
+/// line ending are each taken from the style used most often in the file.
+///
+/// ```
+/// # use aws_config_mod::{AwsConfigFile, SettingPath, Value};
+/// let mut config: AwsConfigFile = "[default]\nregion=us-east-1\n\n\n[profile A]\nregion=us-west-2\n"
+///     .parse()
+///     .unwrap();
+///
+/// config.set(SettingPath::try_from("profile.B.output").unwrap(), Value::from("json")).unwrap();
+///
+/// assert_eq!(
+///     config.to_string(),
+///     "[default]\nregion=us-east-1\n\n\n[profile A]\nregion=us-west-2\n\n\n[profile B]\noutput=json\n"
+/// );
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
 pub struct AwsConfigFile {
+    /// Lines before the first section which could not be parsed in lenient mode. They come before the
+    /// leading whitespace, which holds everything after them.
+    pub(crate) raw_lines: RawLines,
+
     /// Whitespace and comments at the head of the file, before the first section
     pub(crate) leading_whitespace: Whitespace,
 
@@ -30,25 +119,686 @@ pub struct AwsConfigFile {
 
     /// Whitespace and comments at the end of the file, after the end of the last section
     pub(crate) trailing_whitespace: Whitespace,
+
+    /// The formatting detected when the file was parsed, which is used for any lines added to it
+    pub(crate) style: Style,
 }
 
 impl FromStr for AwsConfigFile {
     type Err = crate::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::parse(s)
-            .map(|a| a.1)
-            .map_err(to_owned_input)
-            .map_err(crate::Error::from)
+        Ok(parse_complete::<Self>(s)?)
     }
 }
 
 impl AwsConfigFile {
+    /// Parse a config file without failing on lines that can't be understood. Such lines are kept verbatim,
+    /// inside the [Section] where they appear or at the file level if they come before the first section,
+    /// so that the file is written back out unchanged. After a header which can't be parsed, every line up
+    /// to the next valid header is kept verbatim too. Each of these lines is reported as a [ParseError].
+    ///
+    /// The rest of the file can be read and edited as usual.
+    pub fn parse_lenient(input: &str) -> (Self, Vec<ParseError>) {
+        let mut warnings = vec![];
+
+        #[allow(clippy::unwrap_used)]
+        // This cannot fail because every error is recovered from
+        let (_, (raw_lines, leading_whitespace, sections, trailing_whitespace)) =
+            parse_sections(input, |error| {
+                warnings.push(ParseError::new(input, error));
+                Ok(())
+            })
+            .unwrap();
+
+        let config_file = Self {
+            raw_lines,
+            ..Self::from((leading_whitespace, Some(sections), trailing_whitespace))
+        }
+        .with_detected_style(input);
+
+        (config_file, warnings)
+    }
+
+    /// Lines at the file level, before the first [Section], which could not be parsed in lenient mode.
+    /// See [AwsConfigFile::parse_lenient].
+    pub fn raw_lines(&self) -> impl Iterator<Item = &str> {
+        self.raw_lines.lines()
+    }
+
+    /// The line ending used by this file. New lines are always written with this line ending.
+    pub fn line_ending(&self) -> LineEnding {
+        self.style.line_ending
+    }
+
+    /// Rewrite the spacing, indentation, blank lines and line endings of the whole file as described by the
+    /// [FormatOptions], and sort it if they ask for it. Every line ends with a line break, apart from the last
+    /// line if the options say so. The text of comments and values is left alone.
+    /// Formatting a file which is already formatted doesn't change it. Settings and sections added to the file
+    /// afterwards follow the same style.
+    pub fn format(&mut self, options: &FormatOptions) {
+        let line_ending = self.style.line_ending;
+
+        if options.sort_sections {
+            self.sort_sections_by(|first, second| {
+                (first.get_type(), first.get_name()).cmp(&(second.get_type(), second.get_name()))
+            });
+        }
+
+        format_file(
+            &mut self.raw_lines,
+            &mut self.leading_whitespace,
+            &mut self.sections,
+            &mut self.trailing_whitespace,
+            options,
+            line_ending,
+        );
+
+        self.style = options.style(line_ending);
+    }
+
+    /// Sort the sections of the file with the given comparison function, as described in
+    /// [AwsConfigFile#reordering]. The sort is stable, so sections which compare equal keep their order.
+    pub fn sort_sections_by(
+        &mut self,
+        mut compare: impl FnMut(&Section<ConfigHeader>, &Section<ConfigHeader>) -> Ordering,
+    ) {
+        reorder_sections(&mut self.sections, |sections| {
+            sections.sort_by(|first, second| compare(first, second))
+        })
+    }
+
+    /// Sort the settings within each section of the file with the given comparison function, as described in
+    /// [Section::sort_settings_by]. Settings never move from one section to another.
+    pub fn sort_settings_by(&mut self, mut compare: impl FnMut(&Setting, &Setting) -> Ordering) {
+        self.sections
+            .iter_mut()
+            .for_each(|section| section.sort_settings_by(&mut compare))
+    }
+
+    /// Move the [Section] at position `from` to position `to`, shifting the sections in between, and return a
+    /// mutable reference to it. The positions are those of [AwsConfigFile::sections], and the lines owned by
+    /// the section move with it as described in [AwsConfigFile#reordering]. Returns [None] without changing
+    /// anything if either position is out of range.
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// let mut config: AwsConfigFile = "[profile A]\n\n[profile B]\n\n# the default\n[default]\n"
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// config.move_section(2, 0);
+    ///
+    /// assert_eq!(config.to_string(), "# the default\n[default]\n\n[profile A]\n\n[profile B]\n");
+    /// ```
+    pub fn move_section(&mut self, from: usize, to: usize) -> Option<&mut Section<ConfigHeader>> {
+        move_section(&mut self.sections, from, to)
+    }
+
+    /// Make sure that the last line before the whitespace at the end of the file ends with a line break,
+    /// so that a new section can follow it.
+    fn end_last_line(&mut self) {
+        end_file_line(
+            &mut self.raw_lines,
+            &mut self.leading_whitespace,
+            &mut self.sections,
+            self.style.line_ending,
+        )
+    }
+
+    /// Detect the [Style] of the file it was parsed from, and use it for any lines that are added to the file
+    /// from now on
+    fn with_detected_style(mut self, input: &str) -> Self {
+        self.style = Style::detect(input, &self.sections);
+        self.sections
+            .iter_mut()
+            .for_each(|section| section.style = self.style.clone());
+        self
+    }
+
     /// Return the [AwsConfigFile] to its [String] format. This function simply wraps the [Display] implementation.
     pub fn serialize(&self) -> String {
         self.to_string()
     }
 
+    /// Get whatever the given [ConfigPath] points to, if it exists. Depending on the depth of the path, this is
+    /// a [Section], a [Setting] or a [NestedSetting].
+    pub fn get(&self, config_path: &ConfigPath) -> Option<ConfigItemRef<'_>> {
+        match config_path {
+            ConfigPath::Section(section_path) => {
+                self.get_section(section_path).map(ConfigItemRef::Section)
+            }
+            ConfigPath::Setting(setting_path) => {
+                self.get_setting(setting_path).map(ConfigItemRef::Setting)
+            }
+            ConfigPath::NestedSetting(nested_path) => self
+                .get_nested_setting(nested_path)
+                .map(ConfigItemRef::NestedSetting),
+        }
+    }
+
+    /// Get a mutable reference to whatever the given [ConfigPath] points to, if it exists.
+    pub fn get_mut(&mut self, config_path: &ConfigPath) -> Option<ConfigItemMut<'_>> {
+        let SectionPath {
+            section_type,
+            section_name,
+        } = config_path.section_path();
+        let section = self.get_section_mut(section_type, section_name)?;
+
+        match config_path {
+            ConfigPath::Section(_) => Some(ConfigItemMut::Section(section)),
+            ConfigPath::Setting(setting_path) => section
+                .get_setting_mut(&setting_path.setting_name)
+                .map(ConfigItemMut::Setting),
+            ConfigPath::NestedSetting(nested_path) => section
+                .get_nested_setting_mut(&nested_path.setting_name, &nested_path.nested_setting_name)
+                .map(ConfigItemMut::NestedSetting),
+        }
+    }
+
+    /// Apply a list of [Edit]s to the file in order. Either all of them are applied or, if one of them fails,
+    /// none of them are, and the file is left as it was. Fails with [crate::Error::EditFailed], which holds the
+    /// position of the failed edit and the reason. On success, the [Report] lists what each edit changed.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, Change, Edit, SettingPath, Value, ValueType};
+    /// let mut config: AwsConfigFile = "[profile A]\nregion = us-east-1\n".parse().unwrap();
+    ///
+    /// let edits = [
+    ///     Edit::Set {
+    ///         path: SettingPath::try_from("profile.A.region").unwrap(),
+    ///         value: Value::from("eu-west-1"),
+    ///     },
+    ///     Edit::Remove {
+    ///         path: "profile.B".parse().unwrap(),
+    ///     },
+    /// ];
+    ///
+    /// // profile B doesn't exist, so the first edit isn't applied either
+    /// assert!(config.apply(&edits).is_err());
+    /// assert_eq!(config.to_string(), "[profile A]\nregion = us-east-1\n");
+    ///
+    /// let report = config.apply(&edits[..1]).unwrap();
+    /// assert_eq!(report.changes(), [Change::Updated(ValueType::Single(Value::from("us-east-1")))]);
+    /// assert_eq!(config.to_string(), "[profile A]\nregion = eu-west-1\n");
+    /// ```
+    pub fn apply(&mut self, edits: &[Edit]) -> Result<Report, crate::Error> {
+        let mut config = self.clone();
+        let changes = edits
+            .iter()
+            .enumerate()
+            .map(|(index, edit)| {
+                edit.apply_to(&mut config)
+                    .map_err(|source| crate::Error::EditFailed {
+                        index,
+                        source: Box::new(source),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        *self = config;
+        Ok(Report { changes })
+    }
+
+    /// Remove whatever the given [ConfigPath] points to, returning it if it existed. This behaves like
+    /// [AwsConfigFile::remove_section], [AwsConfigFile::remove_setting] or [AwsConfigFile::remove_nested_setting],
+    /// depending on the depth of the path.
+    pub fn remove(&mut self, config_path: &ConfigPath) -> Option<ConfigItem> {
+        match config_path {
+            ConfigPath::Section(section_path) => {
+                self.remove_section(section_path).map(ConfigItem::Section)
+            }
+            ConfigPath::Setting(setting_path) => {
+                self.remove_setting(setting_path).map(ConfigItem::Setting)
+            }
+            ConfigPath::NestedSetting(nested_path) => self
+                .remove_nested_setting(nested_path)
+                .map(ConfigItem::NestedSetting),
+        }
+    }
+
+    /// The comment lines at the head of the file which don't belong to the first section, without the '#' which
+    /// starts each of them
+    pub fn leading_comments(&self) -> impl Iterator<Item = &str> {
+        self.leading_whitespace.comments()
+    }
+
+    /// Replace the comment lines at the head of the file, such as a notice that the file is managed by a tool.
+    /// A blank line is kept between them and the first section, so that they don't become its comments.
+    pub fn set_leading_comments(&mut self, comments: impl IntoIterator<Item = impl AsRef<str>>) {
+        self.leading_whitespace
+            .set_comments(comments, "", self.style.line_ending);
+
+        if let Some(first) = self.sections.first_mut() {
+            if self.leading_whitespace.comments().next().is_some() {
+                first
+                    .leading_whitespace
+                    .merge_separator(Whitespace::newline(self.style.line_ending));
+            }
+        }
+    }
+
+    /// The comment lines at the end of the file, after the last section, without the '#' which starts each of them
+    pub fn trailing_comments(&self) -> impl Iterator<Item = &str> {
+        self.trailing_whitespace.comments()
+    }
+
+    /// Replace the comment lines at the end of the file, after the last section
+    pub fn set_trailing_comments(&mut self, comments: impl IntoIterator<Item = impl AsRef<str>>) {
+        self.trailing_whitespace
+            .set_comments(comments, "", self.style.line_ending);
+
+        if self.trailing_whitespace.comments().next().is_some() {
+            self.end_last_line()
+        }
+    }
+
+    /// Iterate over every [Section] of the file, in the order they appear
+    pub fn sections(&self) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        self.sections.iter()
+    }
+
+    /// Iterate mutably over every [Section] of the file, in the order they appear
+    pub fn sections_mut(&mut self) -> impl Iterator<Item = &mut Section<ConfigHeader>> {
+        self.sections.iter_mut()
+    }
+
+    /// Iterate over the sections of the given [SectionType], in the order they appear
+    pub fn sections_of(
+        &self,
+        section_type: SectionType,
+    ) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        self.sections
+            .iter()
+            .filter(move |section| section.header.section_type == section_type)
+    }
+
+    /// Iterate mutably over the sections of the given [SectionType], in the order they appear
+    pub fn sections_of_mut(
+        &mut self,
+        section_type: SectionType,
+    ) -> impl Iterator<Item = &mut Section<ConfigHeader>> {
+        self.sections
+            .iter_mut()
+            .filter(move |section| section.header.section_type == section_type)
+    }
+
+    /// Iterate over the profiles of the file, including the default profile. If the file contains both
+    /// `[default]` and `[profile default]`, both are returned; see
+    /// [AwsConfigFile#the-default-profile].
+    pub fn profiles(&self) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        self.sections_of(SectionType::Profile)
+    }
+
+    /// Iterate mutably over the profiles of the file, including the default profile
+    pub fn profiles_mut(&mut self) -> impl Iterator<Item = &mut Section<ConfigHeader>> {
+        self.sections_of_mut(SectionType::Profile)
+    }
+
+    /// Iterate over the `[sso-session]` sections of the file
+    pub fn sso_sessions(&self) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        self.sections_of(SectionType::SsoSession)
+    }
+
+    /// Iterate mutably over the `[sso-session]` sections of the file
+    pub fn sso_sessions_mut(&mut self) -> impl Iterator<Item = &mut Section<ConfigHeader>> {
+        self.sections_of_mut(SectionType::SsoSession)
+    }
+
+    /// Iterate over the `[services]` sections of the file
+    pub fn services(&self) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        self.sections_of(SectionType::Services)
+    }
+
+    /// Iterate mutably over the `[services]` sections of the file
+    pub fn services_mut(&mut self) -> impl Iterator<Item = &mut Section<ConfigHeader>> {
+        self.sections_of_mut(SectionType::Services)
+    }
+
+    /// The names of the profiles in the file, in the order they appear, like `aws configure list-profiles`.
+    /// A `[default]` section which is overridden by `[profile default]` is left out, so that each name is
+    /// only listed once.
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// let config: AwsConfigFile = "[default]\nregion = us-east-1\n[sso-session my-sso]\nsso_region = us-east-1\n[profile dev]\nregion = eu-west-1\n"
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let names: Vec<_> = config.profile_names().map(|name| name.to_string()).collect();
+    ///
+    /// assert_eq!(names, ["default", "dev"]);
+    /// ```
+    pub fn profile_names(&self) -> impl Iterator<Item = &SectionName> {
+        self.visible_sections()
+            .filter(|section| *section.get_type() == SectionType::Profile)
+            .filter_map(|section| section.get_name())
+    }
+
+    /// A strongly typed view of the profile with the given [SectionName], for reading its standard settings, or
+    /// [None] if there is no such profile. See [Profile] for how invalid values are reported.
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// let config: AwsConfigFile = "[default]\nregion = us-east-1\n".parse().unwrap();
+    ///
+    /// let profile = config.profile(&"default".parse().unwrap()).unwrap();
+    ///
+    /// assert_eq!(profile.region().unwrap().unwrap().to_string(), "us-east-1");
+    /// ```
+    pub fn profile(&self, profile_name: &SectionName) -> Option<Profile<&Section<ConfigHeader>>> {
+        let section = self.get_section_inner(&SectionType::Profile, Some(profile_name))?;
+
+        Profile::new(section)
+    }
+
+    /// A strongly typed view of the profile with the given [SectionName], for reading and changing its standard
+    /// settings, or [None] if there is no such profile. Use [AwsConfigFile::section_entry] to create a profile
+    /// first if it may not exist.
+    pub fn profile_mut(
+        &mut self,
+        profile_name: &SectionName,
+    ) -> Option<Profile<&mut Section<ConfigHeader>>> {
+        let section = self.get_section_mut(&SectionType::Profile, &Some(profile_name.clone()))?;
+
+        Profile::new(section)
+    }
+
+    /// A strongly typed view of the `[sso-session]` section with the given [SectionName], or [None] if there is
+    /// no such section
+    pub fn sso_session(
+        &self,
+        session_name: &SectionName,
+    ) -> Option<SsoSession<&Section<ConfigHeader>>> {
+        let section = self.get_section_inner(&SectionType::SsoSession, Some(session_name))?;
+
+        SsoSession::new(section)
+    }
+
+    /// A strongly typed view of the `[sso-session]` section with the given [SectionName], for reading and
+    /// changing its settings, or [None] if there is no such section
+    pub fn sso_session_mut(
+        &mut self,
+        session_name: &SectionName,
+    ) -> Option<SsoSession<&mut Section<ConfigHeader>>> {
+        let section =
+            self.get_section_mut(&SectionType::SsoSession, &Some(session_name.clone()))?;
+
+        SsoSession::new(section)
+    }
+
+    /// Create the `[sso-session]` section with the given [SectionName] and set its `sso_start_url` and
+    /// `sso_region`, returning a view of it. The section is placed as described in
+    /// [AwsConfigFile::insert_section_next_to_reference]. If it already exists, those two settings are changed
+    /// in place.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, Value};
+    /// let mut config: AwsConfigFile = "[profile dev]\nsso_session = my-sso\n".parse().unwrap();
+    ///
+    /// config.insert_sso_session(
+    ///     "my-sso".parse().unwrap(),
+    ///     Value::from("https://my-sso-portal.awsapps.com/start"),
+    ///     "us-east-1".parse().unwrap(),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     config.to_string(),
+    ///     "[profile dev]\nsso_session = my-sso\n\n[sso-session my-sso]\nsso_start_url = https://my-sso-portal.awsapps.com/start\nsso_region = us-east-1\n"
+    /// );
+    /// ```
+    pub fn insert_sso_session(
+        &mut self,
+        session_name: SectionName,
+        sso_start_url: Value,
+        sso_region: Region,
+    ) -> SsoSession<&mut Section<ConfigHeader>> {
+        let section = self.insert_section_next_to_reference(SectionPath {
+            section_type: SectionType::SsoSession,
+            section_name: Some(session_name),
+        });
+
+        let mut sso_session = SsoSession { section };
+        sso_session.set_sso_start_url(sso_start_url);
+        sso_session.set_sso_region(sso_region);
+        sso_session
+    }
+
+    /// The names of the profiles whose `sso_session` setting refers to the `[sso-session]` section with the
+    /// given [SectionName], in the order they appear. The section itself doesn't have to exist.
+    pub fn profiles_using_sso_session<'a>(
+        &'a self,
+        session_name: &'a SectionName,
+    ) -> impl Iterator<Item = &'a SectionName> {
+        self.profiles_referring_to(SectionType::SsoSession, session_name)
+    }
+
+    /// Remove the `[sso-session]` section with the given [SectionName], deciding with [Dependents] what happens
+    /// to the profiles which refer to it through their `sso_session` setting. Returns the removed sections, with
+    /// the session first, or nothing if there is no such session. With [Dependents::Refuse], nothing is removed
+    /// if any profile refers to the session, and the error lists those profiles.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, Dependents};
+    /// let mut config: AwsConfigFile = "[profile dev]\nsso_session = my-sso\n[profile other]\n[sso-session my-sso]\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let session_name = "my-sso".parse().unwrap();
+    ///
+    /// assert!(config.remove_sso_session(&session_name, Dependents::Refuse).is_err());
+    ///
+    /// let removed = config.remove_sso_session(&session_name, Dependents::Cascade).unwrap();
+    ///
+    /// assert_eq!(removed.len(), 2);
+    /// assert_eq!(config.to_string(), "[profile other]\n");
+    /// ```
+    pub fn remove_sso_session(
+        &mut self,
+        session_name: &SectionName,
+        dependents: Dependents,
+    ) -> Result<Vec<Section<ConfigHeader>>, crate::Error> {
+        self.remove_referred_section(SectionType::SsoSession, session_name, dependents)
+    }
+
+    /// A strongly typed view of the `[services]` section with the given [SectionName], or [None] if there is no
+    /// such section
+    pub fn services_section(
+        &self,
+        section_name: &SectionName,
+    ) -> Option<ServicesSection<&Section<ConfigHeader>>> {
+        let section = self.get_section_inner(&SectionType::Services, Some(section_name))?;
+
+        ServicesSection::new(section)
+    }
+
+    /// A strongly typed view of the `[services]` section with the given [SectionName], for reading and changing
+    /// the settings of its services, or [None] if there is no such section
+    pub fn services_section_mut(
+        &mut self,
+        section_name: &SectionName,
+    ) -> Option<ServicesSection<&mut Section<ConfigHeader>>> {
+        let section = self.get_section_mut(&SectionType::Services, &Some(section_name.clone()))?;
+
+        ServicesSection::new(section)
+    }
+
+    /// Create the `[services]` section with the given [SectionName] if it doesn't exist, and return a view of it.
+    /// The section is placed as described in [AwsConfigFile::insert_section_next_to_reference].
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// let mut config: AwsConfigFile = "[profile dev]\nservices = local\n".parse().unwrap();
+    ///
+    /// config
+    ///     .insert_services_section("local".parse().unwrap())
+    ///     .set_endpoint(&"dynamodb".parse().unwrap(), "http://localhost:8000".parse().unwrap())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     config.to_string(),
+    ///     "[profile dev]\nservices = local\n\n[services local]\ndynamodb =\n  endpoint_url = http://localhost:8000\n"
+    /// );
+    /// ```
+    pub fn insert_services_section(
+        &mut self,
+        section_name: SectionName,
+    ) -> ServicesSection<&mut Section<ConfigHeader>> {
+        let section = self.insert_section_next_to_reference(SectionPath {
+            section_type: SectionType::Services,
+            section_name: Some(section_name),
+        });
+
+        ServicesSection { section }
+    }
+
+    /// The names of the profiles whose `services` setting refers to the `[services]` section with the given
+    /// [SectionName], in the order they appear. The section itself doesn't have to exist.
+    pub fn profiles_using_services<'a>(
+        &'a self,
+        section_name: &'a SectionName,
+    ) -> impl Iterator<Item = &'a SectionName> {
+        self.profiles_referring_to(SectionType::Services, section_name)
+    }
+
+    /// Remove the `[services]` section with the given [SectionName], deciding with [Dependents] what happens to
+    /// the profiles which refer to it through their `services` setting, as described in
+    /// [AwsConfigFile::remove_sso_session]
+    pub fn remove_services_section(
+        &mut self,
+        section_name: &SectionName,
+        dependents: Dependents,
+    ) -> Result<Vec<Section<ConfigHeader>>, crate::Error> {
+        self.remove_referred_section(SectionType::Services, section_name, dependents)
+    }
+
+    /// Read the profile with the given [SectionName] into a user-defined type, with the `serde` feature, as
+    /// described in [Section::deserialize]. Fails with [crate::Error::NotFound] if there is no such profile.
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// #[derive(serde::Deserialize, PartialEq, Debug)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Output {
+    ///     Json,
+    ///     Text,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct MyProfile {
+    ///     output: Output,
+    /// }
+    ///
+    /// let config: AwsConfigFile = "[default]\noutput = json\n".parse().unwrap();
+    ///
+    /// let profile = config.profile_as::<MyProfile>(&"default".parse().unwrap()).unwrap();
+    ///
+    /// assert_eq!(profile.output, Output::Json);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn profile_as<'de, D: serde::Deserialize<'de>>(
+        &'de self,
+        profile_name: &SectionName,
+    ) -> Result<D, crate::Error> {
+        let section_path = SectionPath {
+            section_type: SectionType::Profile,
+            section_name: Some(profile_name.clone()),
+        };
+
+        match self.get_section(&section_path) {
+            Some(profile) => profile.deserialize(),
+            None => Err(crate::Error::NotFound(section_path.into())),
+        }
+    }
+
+    /// Compare this file, as the old version, with `other`, as the new version. Only the sections, settings and
+    /// values are compared, so formatting, comments and the order of items are ignored. A bare `[default]`
+    /// which is shadowed by `[profile default]` is left out, as described in [AwsConfigFile#the-default-profile].
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, Difference, SettingPath};
+    /// let old: AwsConfigFile = "[profile A]\nregion = us-east-1\n".parse().unwrap();
+    /// let new: AwsConfigFile = "# reformatted\n[profile A]\nregion=eu-west-1\n".parse().unwrap();
+    ///
+    /// let diff = old.diff(&new);
+    ///
+    /// assert_eq!(
+    ///     diff.differences(),
+    ///     [Difference::Changed {
+    ///         path: SettingPath::try_from("profile.A.region").unwrap().into(),
+    ///         old: Some("us-east-1".into()),
+    ///         new: Some("eu-west-1".into()),
+    ///     }]
+    /// );
+    /// assert!(old.diff(&old).is_empty());
+    /// ```
+    pub fn diff(&self, other: &Self) -> ConfigDiff {
+        diff_sections(self.visible_sections(), other.visible_sections()).masked()
+    }
+
+    /// Merge two files which were both changed from a common `base`, as a version control system does when two
+    /// branches changed the same file. The merged file starts out as `ours`, and the changes which `theirs`
+    /// made to the base are then applied to it item by item:
+    ///
+    /// - sections, settings and nested settings which only `theirs` added, changed or removed are taken over
+    ///   from `theirs`, along with their comments
+    /// - an item which both sides changed in the same way is kept as it is
+    /// - an item which both sides changed in different ways, including one side removing an item which the
+    ///   other changed, is reported as a [crate::MergeConflict] and keeps our version
+    ///
+    /// Comments never conflict: those which only `theirs` changed are taken over, and otherwise ours are kept.
+    /// Items added by `theirs` are placed after the item which precedes them in `theirs`.
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// let base: AwsConfigFile = "[profile A]\nregion = us-east-1\n".parse().unwrap();
+    /// let ours: AwsConfigFile = "# ours\n[profile A]\nregion = us-east-1\noutput = json\n".parse().unwrap();
+    /// let theirs: AwsConfigFile = "[profile A]\nregion = eu-west-1\n".parse().unwrap();
+    ///
+    /// let result = AwsConfigFile::merge(&base, &ours, &theirs);
+    ///
+    /// assert!(result.is_clean());
+    /// assert_eq!(
+    ///     result.merged().to_string(),
+    ///     "# ours\n[profile A]\nregion = eu-west-1\noutput = json\n"
+    /// );
+    /// ```
+    pub fn merge(base: &Self, ours: &Self, theirs: &Self) -> MergeResult {
+        merge(base, ours, theirs)
+    }
+
+    /// Change this file so that it holds the same sections, settings and values as `other`, such as a file
+    /// deserialized from a plain map with the `serde` feature. Only the items which differ are touched, so the
+    /// comments and formatting of everything else stay as they are. Added items are placed after the item
+    /// which precedes them in `other`, and removed items take their comments with them.
+    ///
+    /// ```
+    /// # use aws_config_mod::AwsConfigFile;
+    /// let mut config: AwsConfigFile = "# team A\n[profile A]\nregion = us-east-1 # primary\noutput = json\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let target: AwsConfigFile = "[profile A]\nregion = eu-west-1\n".parse().unwrap();
+    ///
+    /// config.update_from(&target);
+    ///
+    /// assert_eq!(config.to_string(), "# team A\n[profile A]\nregion = eu-west-1 # primary\n");
+    /// ```
+    pub fn update_from(&mut self, other: &Self) {
+        let base = self.clone();
+        merge_into(&base, &base, other, self, Comments::KeepOurs);
+    }
+
+    /// Iterate over the sections which can be found by their path, which leaves out a bare `[default]` if the
+    /// file also has a `[profile default]`
+    pub(crate) fn visible_sections(&self) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        let has_profile_default = self.profiles().any(|profile| {
+            profile.header.is_default_profile() && !profile.header.is_bare_default()
+        });
+
+        self.sections
+            .iter()
+            .filter(move |section| !(has_profile_default && section.header.is_bare_default()))
+    }
+
     // TODO: remove SectionPath as the input. Just accept the section type and optional section name
     // consider making an inner type, GetSectionInput and implement 'Into' for various types
     /// Given a [SectionPath], will find and return the [Section] if it exists; otherwise returns [None].
@@ -93,16 +843,100 @@ impl AwsConfigFile {
     /// Provided a [SettingPath] and a [Value], locates the desired [Setting] and changes its [Value].
     /// If the setting doesn't exist, it will be created. If the [Section] that contains the [Setting]
     /// doesn't exist, it will also be created.
-    pub fn set(&mut self, setting_path: SettingPath, value: Value) {
-        let section = match self.get_section_mut(
-            &setting_path.section_path.section_type,
-            &setting_path.section_path.section_name,
-        ) {
-            Some(section) => section,
-            None => self.insert_section(&setting_path.section_path),
+    ///
+    /// Any [ConfigPath] is accepted as well, so a [NestedSettingPath] behaves like [AwsConfigFile::set_nested].
+    /// Fails with [crate::Error::NotASetting] if the path points to a [Section].
+    pub fn set(&mut self, path: impl Into<ConfigPath>, value: Value) -> Result<(), crate::Error> {
+        let setting_path = match path.into() {
+            ConfigPath::Setting(setting_path) => setting_path,
+            ConfigPath::NestedSetting(nested_path) => return self.set_nested(nested_path, value),
+            config_path @ ConfigPath::Section(_) => {
+                return Err(crate::Error::NotASetting(config_path))
+            }
         };
+        let SettingPath {
+            section_path,
+            setting_name,
+        } = setting_path;
+
+        self.section_entry(section_path)
+            .or_insert()
+            .set(setting_name, value);
+        Ok(())
+    }
+
+    /// Remove the [Section] at the given [SectionPath], returning it if it existed. Its comments go with it,
+    /// as described in [AwsConfigFile#comments-and-blank-lines].
+    pub fn remove_section(&mut self, section_path: &SectionPath) -> Option<Section<ConfigHeader>> {
+        let index = self.section_index(
+            &section_path.section_type,
+            section_path.section_name.as_ref(),
+        )?;
+
+        Some(remove_section_at(&mut self.sections, index))
+    }
+
+    /// Remove the [Setting] at the given [SettingPath], returning it if it existed. Its comments go with it,
+    /// as described in [AwsConfigFile#comments-and-blank-lines].
+    pub fn remove_setting(&mut self, setting_path: &SettingPath) -> Option<Setting> {
+        let SettingPath {
+            section_path,
+            setting_name,
+        } = setting_path;
+
+        self.get_section_mut(&section_path.section_type, &section_path.section_name)?
+            .remove_setting(setting_name)
+    }
+
+    /// Remove the [NestedSetting] at the given [NestedSettingPath], returning it if it existed. Its comments go
+    /// with it, as described in [AwsConfigFile#comments-and-blank-lines]. If it was the last setting in its block,
+    /// the now empty parent [Setting] is removed as well.
+    pub fn remove_nested_setting(
+        &mut self,
+        setting_path: &NestedSettingPath,
+    ) -> Option<NestedSetting> {
+        let NestedSettingPath {
+            section_path,
+            setting_name,
+            nested_setting_name,
+        } = setting_path;
+
+        self.get_section_mut(&section_path.section_type, &section_path.section_name)?
+            .remove_nested_setting(setting_name, nested_setting_name)
+    }
 
-        section.set(setting_path.setting_name, value);
+    /// Provided a [NestedSettingPath] and a [Value], locates the desired [NestedSetting] and changes its [Value].
+    /// Whatever doesn't exist yet is created: the [Section], the parent [Setting] as a block of nested settings,
+    /// and the [NestedSetting] itself, which is indented like the other settings in its block.
+    ///
+    /// Fails with [crate::Error::NotNested] if the parent [Setting] holds a single value. Use
+    /// [AwsConfigFile::convert_to_nested] to replace that value with a block first.
+    pub fn set_nested(
+        &mut self,
+        setting_path: NestedSettingPath,
+        value: Value,
+    ) -> Result<(), crate::Error> {
+        let NestedSettingPath {
+            section_path,
+            setting_name,
+            nested_setting_name,
+        } = setting_path;
+
+        self.insert_section(&section_path)
+            .set_nested(setting_name, nested_setting_name, value)
+    }
+
+    /// Replace the single value of the [Setting] at the given [SettingPath] by an empty block of nested settings,
+    /// so that [AwsConfigFile::set_nested] can add settings under it. Returns the replaced [Value], or [None] if
+    /// the setting doesn't exist or is already nested.
+    pub fn convert_to_nested(&mut self, setting_path: &SettingPath) -> Option<Value> {
+        let SettingPath {
+            section_path,
+            setting_name,
+        } = setting_path;
+
+        self.get_section_mut(&section_path.section_type, &section_path.section_name)?
+            .convert_to_nested(setting_name)
     }
 
     /// Get an immutable reference to a [Section] by its [SectionType] and [SectionName]
@@ -111,10 +945,9 @@ impl AwsConfigFile {
         section_type: &SectionType,
         section_name: Option<&SectionName>,
     ) -> Option<&Section<ConfigHeader>> {
-        self.sections.iter().find(|section| {
-            section.header.section_type == *section_type
-                && section.header.section_name.as_ref() == section_name
-        })
+        let index = self.section_index(section_type, section_name)?;
+
+        self.sections.get(index)
     }
 
     /// Get a mutable reference to a [Section]
@@ -123,23 +956,32 @@ impl AwsConfigFile {
         section_type: &SectionType,
         section_name: &Option<SectionName>,
     ) -> Option<&mut Section<ConfigHeader>> {
-        self.sections.iter_mut().find_map(|section| {
-            if section.header.section_type == *section_type
-                && section.header.section_name == *section_name
-            {
-                Some(section)
-            } else {
-                None
-            }
-        })
+        let index = self.section_index(section_type, section_name.as_ref())?;
+
+        self.sections.get_mut(index)
+    }
+
+    /// Find the position of a [Section] by its [SectionType] and [SectionName]. If the file has both a
+    /// `[default]` and a `[profile default]` section, the latter is found.
+    pub(crate) fn section_index(
+        &self,
+        section_type: &SectionType,
+        section_name: Option<&SectionName>,
+    ) -> Option<usize> {
+        let is_match = |section: &Section<ConfigHeader>| {
+            section.header.section_type == *section_type
+                && section.header.section_name.as_ref() == section_name
+        };
+
+        self.sections
+            .iter()
+            .position(|section| is_match(section) && !section.header.is_bare_default())
+            .or_else(|| self.sections.iter().position(is_match))
     }
 
     /// Check if the given [Section] exists from a [SectionPath]
     pub(crate) fn contains_section(&self, section_path: &SectionPath) -> bool {
-        self.sections.iter().any(|section| {
-            section.get_name() == section_path.section_name.as_ref()
-                && *section.get_type() == section_path.section_type
-        })
+        self.get_section(section_path).is_some()
     }
 
     /// Given a [SectionPath], create the [Section] if it doesn't exist and return a mutable
@@ -149,9 +991,7 @@ impl AwsConfigFile {
         section_path: &SectionPath,
     ) -> &mut Section<ConfigHeader> {
         if !self.contains_section(section_path) {
-            let new_section: Section<ConfigHeader> =
-                Section::new(ConfigHeader::from(section_path.clone()));
-            self.sections.push(new_section);
+            self.push_section(section_path.clone());
         }
 
         #[allow(clippy::unwrap_used)]
@@ -159,13 +999,224 @@ impl AwsConfigFile {
         self.get_section_mut(&section_path.section_type, &section_path.section_name)
             .unwrap()
     }
+
+    /// Look up the [Section] at the given [SectionPath] once, returning a [SectionEntry] which can be used to
+    /// change the [Section] if it exists or to create it otherwise.
+    pub fn section_entry(&mut self, section_path: SectionPath) -> SectionEntry<'_> {
+        let index = self.section_index(
+            &section_path.section_type,
+            section_path.section_name.as_ref(),
+        );
+
+        match index {
+            Some(index) => SectionEntry::Occupied(OccupiedSectionEntry {
+                sections: &mut self.sections,
+                index,
+                section_path,
+            }),
+            None => SectionEntry::Vacant(VacantSectionEntry {
+                config: self,
+                section_path,
+            }),
+        }
+    }
+
+    /// Add a [Section] without any settings for the given [SectionPath] to the end of the file, without
+    /// checking whether it already exists
+    pub(crate) fn push_section(&mut self, section_path: SectionPath) -> &mut Section<ConfigHeader> {
+        self.insert_section_at(self.sections.len(), section_path)
+    }
+
+    /// Create the [Section] at the given [SectionPath] directly in front of the section at `anchor`, and return
+    /// a mutable reference to it. It takes over the blank lines above the section at `anchor`, and the comments
+    /// above that section stay with it. If the [Section] already exists, it is returned where it is. Returns
+    /// [None] without changing anything if there is no section at `anchor`.
+    pub fn insert_section_before(
+        &mut self,
+        anchor: &SectionPath,
+        section_path: SectionPath,
+    ) -> Option<&mut Section<ConfigHeader>> {
+        let index = self.section_index(&anchor.section_type, anchor.section_name.as_ref())?;
+
+        Some(self.insert_section_or_get(index, section_path))
+    }
+
+    /// Create the [Section] at the given [SectionPath] directly after the section at `anchor`, and return a
+    /// mutable reference to it. If the [Section] already exists, it is returned where it is. Returns [None]
+    /// without changing anything if there is no section at `anchor`.
+    pub fn insert_section_after(
+        &mut self,
+        anchor: &SectionPath,
+        section_path: SectionPath,
+    ) -> Option<&mut Section<ConfigHeader>> {
+        let index = self.section_index(&anchor.section_type, anchor.section_name.as_ref())?;
+
+        Some(self.insert_section_or_get(index + 1, section_path))
+    }
+
+    /// Create a `[services]` or `[sso-session]` section directly after the first profile which refers to it
+    /// through its `services` or `sso_session` setting, so that the two can be read together, and return a
+    /// mutable reference to it. If no profile refers to it, or it is another type of section, it is added to
+    /// the end of the file. If the [Section] already exists, it is returned where it is.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, SectionPath, Value};
+    /// let mut config: AwsConfigFile = "[profile A]\nservices = local\n\n[profile B]\nregion = us-east-1\n"
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let section_path = SectionPath::try_from("services.local").unwrap();
+    /// config.insert_section_next_to_reference(section_path);
+    ///
+    /// assert_eq!(
+    ///     config.to_string(),
+    ///     "[profile A]\nservices = local\n\n[services local]\n\n[profile B]\nregion = us-east-1\n"
+    /// );
+    /// ```
+    pub fn insert_section_next_to_reference(
+        &mut self,
+        section_path: SectionPath,
+    ) -> &mut Section<ConfigHeader> {
+        let index = reference_setting(&section_path.section_type)
+            .zip(section_path.section_name.as_ref())
+            .and_then(|(reference, section_name)| {
+                self.sections
+                    .iter()
+                    .position(|section| refers_to(section, reference, section_name))
+            })
+            .map(|index| index + 1)
+            .unwrap_or(self.sections.len());
+
+        self.insert_section_or_get(index, section_path)
+    }
+
+    /// The names of the profiles which refer to the section of the given [SectionType] and [SectionName], such
+    /// as through their `sso_session` setting, in the order they appear
+    fn profiles_referring_to<'a>(
+        &'a self,
+        section_type: SectionType,
+        section_name: &'a SectionName,
+    ) -> impl Iterator<Item = &'a SectionName> {
+        let reference = reference_setting(&section_type);
+
+        self.visible_sections()
+            .filter(move |section| {
+                reference.is_some_and(|reference| refers_to(section, reference, section_name))
+            })
+            .filter_map(|section| section.get_name())
+    }
+
+    /// Remove the section of the given [SectionType] and [SectionName], along with the profiles which refer to it
+    /// if [Dependents::Cascade] is given, and return the removed sections with the referred section first
+    fn remove_referred_section(
+        &mut self,
+        section_type: SectionType,
+        section_name: &SectionName,
+        dependents: Dependents,
+    ) -> Result<Vec<Section<ConfigHeader>>, crate::Error> {
+        let Some(index) = self.section_index(&section_type, Some(section_name)) else {
+            return Ok(vec![]);
+        };
+
+        let profiles = self
+            .profiles_referring_to(section_type.clone(), section_name)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if dependents == Dependents::Refuse && !profiles.is_empty() {
+            let section_path = SectionPath {
+                section_type,
+                section_name: Some(section_name.clone()),
+            };
+            return Err(crate::Error::StillReferenced {
+                section_path: section_path.into(),
+                profiles,
+            });
+        }
+
+        let mut removed = vec![remove_section_at(&mut self.sections, index)];
+        for profile_name in profiles {
+            if let Some(index) = self.section_index(&SectionType::Profile, Some(&profile_name)) {
+                removed.push(remove_section_at(&mut self.sections, index));
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Create the [Section] at the given position if it doesn't exist, and return a mutable reference to it
+    fn insert_section_or_get(
+        &mut self,
+        index: usize,
+        section_path: SectionPath,
+    ) -> &mut Section<ConfigHeader> {
+        let existing = self.section_index(
+            &section_path.section_type,
+            section_path.section_name.as_ref(),
+        );
+
+        match existing {
+            Some(existing) => &mut self.sections[existing],
+            None => self.insert_section_at(index, section_path),
+        }
+    }
+
+    /// Add a [Section] without any settings for the given [SectionPath] at the given position, without checking
+    /// whether it already exists
+    fn insert_section_at(
+        &mut self,
+        index: usize,
+        section_path: SectionPath,
+    ) -> &mut Section<ConfigHeader> {
+        let section = Section::with_style(ConfigHeader::from(section_path), &self.style);
+
+        self.place_section(index, section)
+    }
+
+    /// Put a [Section] at the given position, without checking whether it already exists, and return a mutable
+    /// reference to it. The section keeps its comments, but takes over the [Style] of this file.
+    pub(crate) fn place_section(
+        &mut self,
+        index: usize,
+        mut section: Section<ConfigHeader>,
+    ) -> &mut Section<ConfigHeader> {
+        section.style = self.style.clone();
+        if index == self.sections.len() {
+            self.end_last_line();
+        }
+
+        insert_section_at(
+            &mut self.sections,
+            index,
+            section,
+            !self.leading_whitespace.is_empty() || !self.raw_lines.is_empty(),
+        )
+    }
+}
+
+/// The name of the profile setting which refers to sections of the given [SectionType], if there is one
+fn reference_setting(section_type: &SectionType) -> Option<&'static str> {
+    match section_type {
+        SectionType::Services => Some("services"),
+        SectionType::SsoSession => Some("sso_session"),
+        _ => None,
+    }
+}
+
+/// Whether the section is a profile whose `reference` setting holds the given [SectionName]
+fn refers_to(section: &Section<ConfigHeader>, reference: &str, section_name: &SectionName) -> bool {
+    *section.get_type() == SectionType::Profile
+        && section
+            .single_value(reference)
+            .is_some_and(|value| *value == **section_name)
 }
 
 impl Display for AwsConfigFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}{}{}",
+            "{}{}{}{}",
+            self.raw_lines,
             self.leading_whitespace,
             self.sections
                 .iter()
@@ -179,20 +1230,17 @@ impl Display for AwsConfigFile {
 impl<'a> Parsable<'a> for AwsConfigFile {
     type Output = Self;
 
-    fn parse(input: &'a str) -> IResult<&'a str, Self::Output, VerboseError<&'a str>> {
-        map(
-            map(
-                tuple((
-                    Whitespace::parse,
-                    opt(many0(Section::<ConfigHeader>::parse)),
-                    Whitespace::parse,
-                )),
-                Self::from,
-            )
-            .and(eof),
-            |(config, _)| config,
-        )
-        .parse(input)
+    fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
+        let (next, (raw_lines, leading_whitespace, sections, trailing_whitespace)) =
+            parse_sections(input, Err)?;
+
+        let config_file = Self {
+            raw_lines,
+            ..Self::from((leading_whitespace, Some(sections), trailing_whitespace))
+        }
+        .with_detected_style(input);
+
+        Ok((next, config_file))
     }
 }
 
@@ -205,9 +1253,11 @@ impl From<(Whitespace, Option<Vec<Section<ConfigHeader>>>, Whitespace)> for AwsC
         ),
     ) -> Self {
         Self {
+            raw_lines: RawLines::default(),
             leading_whitespace,
             sections: sections.unwrap_or_default(),
             trailing_whitespace,
+            style: Style::default(),
         }
     }
 }
@@ -215,7 +1265,7 @@ impl From<(Whitespace, Option<Vec<Section<ConfigHeader>>>, Whitespace)> for AwsC
 #[cfg(test)]
 mod test {
     use super::AwsConfigFile;
-    use crate::lexer::Parsable;
+    use crate::{lexer::Parsable, LineEnding};
     use nom::Finish;
 
     const SAMPLE_CONFIG_FILE: &str = r#"
@@ -298,6 +1348,25 @@ dynamodb =
         AwsConfigFile::parse("").finish().ok().unwrap();
     }
 
+    #[test]
+    fn crlf_config_round_trips() {
+        let input = "# leading comment\r\n[default] # header comment\r\nregion=us-west-2\r\n\r\n# section comment\r\n[services my-services]\r\ndynamodb = \r\n  endpoint_url = http://localhost:8000 # nested comment\r\n  # trailing nested comment\r\n";
+
+        let (next, config) = AwsConfigFile::parse(input).expect("Should be valid");
+        assert!(next.is_empty());
+        assert_eq!(config.line_ending(), LineEnding::CrLf);
+
+        let nested = config.sections[1].settings[0].value();
+        match nested {
+            crate::ValueType::Single(_) => panic!("Should be nested"),
+            crate::ValueType::Nested(nested) => {
+                assert_eq!(nested[0].value(), "http://localhost:8000")
+            }
+        }
+
+        assert_eq!(config.to_string(), input)
+    }
+
     #[test]
     fn multiline_whitespace_is_valid() {
         let input = r#"
@@ -0,0 +1,43 @@
+//! Contains the items that a [crate::ConfigPath] can point to. Since a path can have different depths, looking
+//! one up gives back an enum of a section, a setting or a nested setting.
+
+use super::{header::ConfigHeader, NestedSetting, Section, Setting};
+
+/// An item that was taken out of an [crate::AwsConfigFile], as returned by [crate::AwsConfigFile::remove]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConfigItem {
+    /// A whole [Section]
+    Section(Section<ConfigHeader>),
+
+    /// A [Setting]
+    Setting(Setting),
+
+    /// A [NestedSetting]
+    NestedSetting(NestedSetting),
+}
+
+/// A reference to an item in an [crate::AwsConfigFile], as returned by [crate::AwsConfigFile::get]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigItemRef<'a> {
+    /// A whole [Section]
+    Section(&'a Section<ConfigHeader>),
+
+    /// A [Setting]
+    Setting(&'a Setting),
+
+    /// A [NestedSetting]
+    NestedSetting(&'a NestedSetting),
+}
+
+/// A mutable reference to an item in an [crate::AwsConfigFile], as returned by [crate::AwsConfigFile::get_mut]
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum ConfigItemMut<'a> {
+    /// A whole [Section]
+    Section(&'a mut Section<ConfigHeader>),
+
+    /// A [Setting]
+    Setting(&'a mut Setting),
+
+    /// A [NestedSetting]
+    NestedSetting(&'a mut NestedSetting),
+}
@@ -0,0 +1,180 @@
+//! Contains items related to parsing and stringifying config paths. A config path can point to a section,
+//! a setting, or a nested setting, so it is a single way to address anything in a configuration file.
+
+use super::{
+    section_path::unnamed_section_path, NestedSettingPath, SectionPath, SettingName, SettingPath,
+};
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{cut, opt},
+    error::context,
+    sequence::preceded,
+};
+use std::{fmt::Display, str::FromStr};
+
+/// A path to a [crate::Section], a [crate::Setting], or a [crate::NestedSetting], depending on its depth.
+/// The string form is the [SectionPath] followed by up to two [SettingName]s, all separated by '.':
+///
+/// - `profile.A` points to the section `[profile A]`
+/// - `profile.A.s3` points to the setting `s3` in that section
+/// - `profile.A.s3.max_queue_size` points to the setting `max_queue_size` nested under `s3`
+///
+/// Sections without a name, such as `[preview]`, are addressed by their type alone, so `preview.cloudfront`
+/// points to a setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigPath {
+    /// The path to a [crate::Section]
+    Section(SectionPath),
+
+    /// The path to a [crate::Setting]
+    Setting(SettingPath),
+
+    /// The path to a [crate::NestedSetting]
+    NestedSetting(NestedSettingPath),
+}
+
+impl ConfigPath {
+    /// The path of the [crate::Section] that contains whatever this path points to
+    pub fn section_path(&self) -> &SectionPath {
+        match self {
+            ConfigPath::Section(section_path) => section_path,
+            ConfigPath::Setting(setting_path) => &setting_path.section_path,
+            ConfigPath::NestedSetting(nested_path) => &nested_path.section_path,
+        }
+    }
+}
+
+impl From<SectionPath> for ConfigPath {
+    fn from(section_path: SectionPath) -> Self {
+        Self::Section(section_path)
+    }
+}
+
+impl From<SettingPath> for ConfigPath {
+    fn from(setting_path: SettingPath) -> Self {
+        Self::Setting(setting_path)
+    }
+}
+
+impl From<NestedSettingPath> for ConfigPath {
+    fn from(nested_path: NestedSettingPath) -> Self {
+        Self::NestedSetting(nested_path)
+    }
+}
+
+impl TryFrom<&str> for ConfigPath {
+    type Error = crate::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for ConfigPath {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse_complete::<Self>(s)?)
+    }
+}
+
+impl Display for ConfigPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigPath::Section(section_path) => write!(f, "{section_path}"),
+            ConfigPath::Setting(setting_path) => write!(f, "{setting_path}"),
+            ConfigPath::NestedSetting(nested_path) => write!(f, "{nested_path}"),
+        }
+    }
+}
+
+impl<'a> Parsable<'a> for ConfigPath {
+    type Output = Self;
+
+    fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
+        let (next, section_path) = alt((unnamed_section_path, SectionPath::parse))(input)?;
+        let (next, setting_name) = opt(setting_segment)(next)?;
+
+        let Some(setting_name) = setting_name else {
+            return Ok((next, Self::Section(section_path)));
+        };
+
+        let (next, nested_setting_name) = opt(setting_segment)(next)?;
+
+        let config_path = match nested_setting_name {
+            Some(nested_setting_name) => Self::NestedSetting(NestedSettingPath {
+                section_path,
+                setting_name,
+                nested_setting_name,
+            }),
+            None => Self::Setting(SettingPath {
+                section_path,
+                setting_name,
+            }),
+        };
+
+        Ok((next, config_path))
+    }
+}
+
+/// Parses a '.' followed by a [SettingName]. Once the '.' is found, a missing name is an error rather than
+/// the end of the path.
+fn setting_segment(input: &str) -> ParserOutput<'_, SettingName> {
+    preceded(
+        context(Expected::PathSeparator.label(), tag(".")),
+        cut(SettingName::parse),
+    )(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConfigPath;
+    use crate::Expected;
+
+    #[test]
+    fn paths_of_every_depth_round_trip() {
+        for path in [
+            "profile.A",
+            "profile.A.region",
+            "services.my-services.ec2.endpoint_url",
+            "preview",
+            "preview.cloudfront",
+            "plugins.cli_legacy_plugin_path",
+        ] {
+            let config_path = path.parse::<ConfigPath>().expect("Should be valid");
+
+            assert_eq!(config_path.to_string(), path)
+        }
+    }
+
+    #[test]
+    fn depth_decides_the_variant() {
+        let config_path = "sso-session.my-sso".parse::<ConfigPath>();
+        assert!(matches!(config_path, Ok(ConfigPath::Section(_))));
+
+        let config_path = "preview.cloudfront".parse::<ConfigPath>();
+        assert!(matches!(config_path, Ok(ConfigPath::Setting(_))));
+
+        let config_path = "profile.A.s3.max_queue_size".parse::<ConfigPath>();
+        assert!(matches!(config_path, Ok(ConfigPath::NestedSetting(_))));
+    }
+
+    #[test]
+    fn rejects_incomplete_and_too_deep_paths() {
+        let error = match "profile.A.s3.".parse::<ConfigPath>() {
+            Err(crate::Error::ParseError(error)) => error,
+            other => panic!("Should not be valid: {other:?}"),
+        };
+        assert_eq!(error.expected(), Expected::SettingName);
+        assert_eq!(error.column(), 14);
+
+        let error = match "profile.A.s3.max_queue_size.x".parse::<ConfigPath>() {
+            Err(crate::Error::ParseError(error)) => error,
+            other => panic!("Should not be valid: {other:?}"),
+        };
+        assert_eq!(error.expected(), Expected::EndOfInput);
+    }
+}
@@ -0,0 +1,56 @@
+//! Contains the [CredentialSource] type, which is the typed value of the `credential_source` setting.
+
+use crate::Error;
+use std::{fmt::Display, str::FromStr};
+
+/// Where the AWS CLI finds the credentials it uses to assume the role of a profile, as an alternative to a
+/// `source_profile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CredentialSource {
+    /// `Environment`, which reads them from environment variables
+    Environment,
+
+    /// `Ec2InstanceMetadata`, which uses the role of the EC2 instance
+    Ec2InstanceMetadata,
+
+    /// `EcsContainer`, which uses the role of the ECS task
+    EcsContainer,
+}
+
+impl CredentialSource {
+    /// Every variant, used to find one by its name
+    const ALL: [Self; 3] = [
+        Self::Environment,
+        Self::Ec2InstanceMetadata,
+        Self::EcsContainer,
+    ];
+
+    /// The name of the source, as it is written in the config file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Environment => "Environment",
+            Self::Ec2InstanceMetadata => "Ec2InstanceMetadata",
+            Self::EcsContainer => "EcsContainer",
+        }
+    }
+}
+
+impl FromStr for CredentialSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|source| source.as_str() == s)
+            .ok_or_else(|| Error::InvalidValue {
+                value: s.to_string(),
+                expected: "one of Environment, Ec2InstanceMetadata or EcsContainer",
+            })
+    }
+}
+
+impl Display for CredentialSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
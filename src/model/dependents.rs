@@ -0,0 +1,14 @@
+//! Contains the [Dependents] type, which decides what happens to the profiles which refer to a section being
+//! removed.
+
+/// What to do with the profiles which refer to a section that is being removed, such as the profiles whose
+/// `sso_session` setting names an `[sso-session]` section passed to [crate::AwsConfigFile::remove_sso_session]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dependents {
+    /// Leave everything as it is and fail with [crate::Error::StillReferenced] if any profile refers to the
+    /// section
+    Refuse,
+
+    /// Remove the profiles which refer to the section along with it
+    Cascade,
+}
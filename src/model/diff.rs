@@ -0,0 +1,429 @@
+//! Contains the semantic difference between two files, as returned by [crate::AwsConfigFile::diff] and
+//! [crate::AwsCredentialsFile::diff]. Only the sections, settings and values are compared, so changes to
+//! whitespace, comments and the order of items don't show up.
+
+use super::{
+    header::Header, nested_settings::NestedSettings, ConfigPath, NestedSetting, NestedSettingPath,
+    Section, SectionPath, Setting, SettingName, SettingPath, Value, ValueType,
+};
+use std::fmt::Display;
+
+/// The names of the settings which hold secrets. Their values are masked in a [ConfigDiff].
+const SECRET_SETTINGS: [&str; 3] = [
+    "aws_secret_access_key",
+    "aws_session_token",
+    "aws_security_token",
+];
+
+/// What the value of a secret setting is replaced by in a [ConfigDiff]
+const MASK: &str = "********";
+
+/// The differences between an old and a new version of a file, in the order they appear in the old file,
+/// followed by whatever was added in the new one.
+///
+/// An item which was added or removed is listed once, without listing its contents separately. The values
+/// of settings which hold secrets, such as `aws_secret_access_key`, are masked, so that a diff can be shown
+/// or logged safely.
+///
+/// The [Display] implementation renders one difference per line:
+///
+/// ```
+/// # use aws_config_mod::AwsConfigFile;
+/// let old: AwsConfigFile = "[profile B]\nrole_arn = X\nregion = us-east-1\n".parse().unwrap();
+/// let new: AwsConfigFile = "[profile B]  # moved\nrole_arn=Y\n\n[profile C]\n".parse().unwrap();
+///
+/// assert_eq!(
+///     old.diff(&new).to_string(),
+///     "profile B: role_arn changed from X to Y\nprofile B: region removed\nprofile C added\n"
+/// );
+/// ```
+///
+/// With the `serde` feature, a [ConfigDiff] can be written as JSON. It is a list of differences, each of
+/// which names its kind in the `change` field:
+///
+/// ```json
+/// [
+///     { "change": "changed", "path": "profile.B.role_arn", "old": "X", "new": "Y" },
+///     { "change": "removed", "path": "profile.B.region", "value": "us-east-1" },
+///     { "change": "added", "path": "profile.C", "value": null }
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ConfigDiff {
+    /// The differences, in the order described above
+    pub(crate) differences: Vec<Difference>,
+}
+
+impl ConfigDiff {
+    /// The differences between the two files
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    /// Whether the two files are the same, apart from formatting and comments
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl Display for ConfigDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.differences
+            .iter()
+            .try_for_each(|difference| writeln!(f, "{difference}"))
+    }
+}
+
+/// A single difference between two files. The value of a [Setting] is [None] if it is a block of nested
+/// settings, and the value of a [Section] is always [None].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "change", rename_all = "snake_case")
+)]
+pub enum Difference {
+    /// An item which only exists in the new file
+    Added {
+        /// The path of the item
+        path: ConfigPath,
+
+        /// Its value in the new file
+        value: Option<Value>,
+    },
+
+    /// An item which only exists in the old file
+    Removed {
+        /// The path of the item
+        path: ConfigPath,
+
+        /// Its value in the old file
+        value: Option<Value>,
+    },
+
+    /// A setting whose value is different in the two files. If a setting with a single value was replaced
+    /// by a block of nested settings, or the other way around, one side is [None].
+    Changed {
+        /// The path of the setting
+        path: ConfigPath,
+
+        /// Its value in the old file
+        old: Option<Value>,
+
+        /// Its value in the new file
+        new: Option<Value>,
+    },
+}
+
+impl Difference {
+    /// The path of the item which is different
+    pub fn path(&self) -> &ConfigPath {
+        match self {
+            Difference::Added { path, .. }
+            | Difference::Removed { path, .. }
+            | Difference::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let section_path = self.path().section_path();
+        let section = match &section_path.section_name {
+            Some(section_name) => format!("{} {section_name}", section_path.section_type),
+            None => section_path.section_type.to_string(),
+        };
+        let item = match self.path() {
+            ConfigPath::Section(_) => None,
+            ConfigPath::Setting(setting_path) => Some(setting_path.setting_name.to_string()),
+            ConfigPath::NestedSetting(nested_path) => Some(format!(
+                "{}.{}",
+                nested_path.setting_name, nested_path.nested_setting_name
+            )),
+        };
+        let describe = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => String::from("nested settings"),
+        };
+
+        match (item, self) {
+            (None, Difference::Added { .. }) => write!(f, "{section} added"),
+            (None, Difference::Removed { .. }) => write!(f, "{section} removed"),
+            (None, Difference::Changed { .. }) => write!(f, "{section} changed"),
+            (Some(item), Difference::Added { value: None, .. }) => {
+                write!(f, "{section}: {item} added")
+            }
+            (Some(item), Difference::Added { value, .. }) => {
+                write!(f, "{section}: {item} added as {}", describe(value))
+            }
+            (Some(item), Difference::Removed { .. }) => write!(f, "{section}: {item} removed"),
+            (Some(item), Difference::Changed { old, new, .. }) => write!(
+                f,
+                "{section}: {item} changed from {} to {}",
+                describe(old),
+                describe(new)
+            ),
+        }
+    }
+}
+
+/// Compare the sections of an old and a new file. Each file must only yield the sections which lookups find,
+/// so that a section which is shadowed by another with the same path isn't compared. If a file holds the
+/// same path more than once anyway, only the first one is compared. The values are not masked yet, which is
+/// left to [ConfigDiff::masked].
+pub(crate) fn diff_sections<'a, T: Header + 'a>(
+    old: impl Iterator<Item = &'a Section<T>>,
+    new: impl Iterator<Item = &'a Section<T>>,
+) -> ConfigDiff {
+    let old = sections_by_path(old);
+    let new = sections_by_path(new);
+    let differences = keys(&[&old, &new])
+        .into_iter()
+        .flat_map(|path| diff_section(path, find(&old, path), find(&new, path)))
+        .collect();
+
+    ConfigDiff { differences }
+}
+
+/// Compare a section of an old and a new file, either of which may not have it
+pub(crate) fn diff_section<T>(
+    section_path: &SectionPath,
+    old: Option<&Section<T>>,
+    new: Option<&Section<T>>,
+) -> Vec<Difference> {
+    let path = || ConfigPath::Section(section_path.clone());
+
+    match (old, new) {
+        (Some(old), Some(new)) => {
+            let old = settings_by_name(old);
+            let new = settings_by_name(new);
+
+            keys(&[&old, &new])
+                .into_iter()
+                .flat_map(|setting_name| {
+                    let setting_path = SettingPath {
+                        section_path: section_path.clone(),
+                        setting_name: (*setting_name).clone(),
+                    };
+                    diff_setting(
+                        &setting_path,
+                        find(&old, setting_name),
+                        find(&new, setting_name),
+                    )
+                })
+                .collect()
+        }
+        (Some(_), None) => vec![Difference::Removed {
+            path: path(),
+            value: None,
+        }],
+        (None, Some(_)) => vec![Difference::Added {
+            path: path(),
+            value: None,
+        }],
+        (None, None) => vec![],
+    }
+}
+
+/// Compare a setting of an old and a new file, either of which may not have it. If it holds a block of
+/// nested settings in both, the nested settings are compared one by one.
+pub(crate) fn diff_setting(
+    setting_path: &SettingPath,
+    old: Option<&Setting>,
+    new: Option<&Setting>,
+) -> Vec<Difference> {
+    let path = || ConfigPath::Setting(setting_path.clone());
+
+    match (old, new) {
+        (Some(old), Some(new)) => match (&old.value, &new.value) {
+            (ValueType::Single(old_value), ValueType::Single(new_value))
+                if old_value == new_value =>
+            {
+                vec![]
+            }
+            (ValueType::Nested(old_nested), ValueType::Nested(new_nested)) => {
+                let old = nested_settings_by_name(old_nested);
+                let new = nested_settings_by_name(new_nested);
+
+                keys(&[&old, &new])
+                    .into_iter()
+                    .filter_map(|nested_setting_name| {
+                        let nested_path = NestedSettingPath {
+                            section_path: setting_path.section_path.clone(),
+                            setting_name: setting_path.setting_name.clone(),
+                            nested_setting_name: (*nested_setting_name).clone(),
+                        };
+                        diff_nested_setting(
+                            &nested_path,
+                            find(&old, nested_setting_name),
+                            find(&new, nested_setting_name),
+                        )
+                    })
+                    .collect()
+            }
+            _ => vec![Difference::Changed {
+                path: path(),
+                old: single_value(old),
+                new: single_value(new),
+            }],
+        },
+        (Some(old), None) => vec![Difference::Removed {
+            path: path(),
+            value: single_value(old),
+        }],
+        (None, Some(new)) => vec![Difference::Added {
+            path: path(),
+            value: single_value(new),
+        }],
+        (None, None) => vec![],
+    }
+}
+
+/// Compare a nested setting of an old and a new file, either of which may not have it
+pub(crate) fn diff_nested_setting(
+    nested_path: &NestedSettingPath,
+    old: Option<&NestedSetting>,
+    new: Option<&NestedSetting>,
+) -> Option<Difference> {
+    let path = ConfigPath::NestedSetting(nested_path.clone());
+
+    match (old.map(NestedSetting::value), new.map(NestedSetting::value)) {
+        (Some(old), Some(new)) if old == new => None,
+        (Some(old), Some(new)) => Some(Difference::Changed {
+            path,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        (Some(old), None) => Some(Difference::Removed {
+            path,
+            value: Some(old.clone()),
+        }),
+        (None, Some(new)) => Some(Difference::Added {
+            path,
+            value: Some(new.clone()),
+        }),
+        (None, None) => None,
+    }
+}
+
+impl ConfigDiff {
+    /// Replace the values of the settings which hold secrets by a mask
+    pub(crate) fn masked(self) -> Self {
+        let differences = self
+            .differences
+            .into_iter()
+            .map(Difference::masked)
+            .collect();
+
+        Self { differences }
+    }
+}
+
+impl Difference {
+    /// Replace the values by a mask if this is the difference of a setting which holds a secret
+    pub(crate) fn masked(self) -> Self {
+        let setting_name = match self.path() {
+            ConfigPath::Section(_) => return self,
+            ConfigPath::Setting(setting_path) => &setting_path.setting_name,
+            ConfigPath::NestedSetting(nested_path) => &nested_path.nested_setting_name,
+        };
+        if !SECRET_SETTINGS.contains(&&**setting_name) {
+            return self;
+        }
+
+        let mask = |value: Option<Value>| value.map(|_| Value::from(MASK));
+        match self {
+            Difference::Added { path, value } => Difference::Added {
+                path,
+                value: mask(value),
+            },
+            Difference::Removed { path, value } => Difference::Removed {
+                path,
+                value: mask(value),
+            },
+            Difference::Changed { path, old, new } => Difference::Changed {
+                path,
+                old: mask(old),
+                new: mask(new),
+            },
+        }
+    }
+}
+
+/// The sections of a file by their [SectionPath], keeping only the first section for each path
+pub(crate) fn sections_by_path<'a, T: Header + 'a>(
+    sections: impl Iterator<Item = &'a Section<T>>,
+) -> Vec<(SectionPath, &'a Section<T>)> {
+    unique(sections.map(|section| {
+        let section_path = SectionPath {
+            section_type: section.header.section_type().clone(),
+            section_name: section.header.section_name().cloned(),
+        };
+        (section_path, section)
+    }))
+}
+
+/// The settings of a section by their [SettingName], keeping only the first setting for each name
+pub(crate) fn settings_by_name<T>(section: &Section<T>) -> Vec<(&SettingName, &Setting)> {
+    unique(
+        section
+            .settings
+            .iter()
+            .map(|setting| (setting.name(), setting)),
+    )
+}
+
+/// The nested settings of a block by their [SettingName], keeping only the first one for each name
+pub(crate) fn nested_settings_by_name(
+    nested_settings: &NestedSettings,
+) -> Vec<(&SettingName, &NestedSetting)> {
+    unique(nested_settings.iter().map(|nested| (nested.name(), nested)))
+}
+
+/// The keys of all the given lists, in the order of the first list, followed by the keys which only appear in
+/// later lists in their order
+pub(crate) fn keys<'a, K: PartialEq, V>(lists: &[&'a [(K, V)]]) -> Vec<&'a K> {
+    let mut keys: Vec<&K> = vec![];
+
+    for (key, _) in lists.iter().flat_map(|list| list.iter()) {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    keys
+}
+
+/// Find the item with the given key
+pub(crate) fn find<K: PartialEq, V: Copy>(items: &[(K, V)], key: &K) -> Option<V> {
+    items
+        .iter()
+        .find(|(existing, _)| existing == key)
+        .map(|(_, item)| *item)
+}
+
+/// The value of a setting which holds a single value
+fn single_value(setting: &Setting) -> Option<Value> {
+    match &setting.value {
+        ValueType::Single(value) => Some(value.clone()),
+        ValueType::Nested(_) => None,
+    }
+}
+
+/// Collect the given items, keeping only the first item for each key
+fn unique<K: PartialEq, V: Copy>(items: impl Iterator<Item = (K, V)>) -> Vec<(K, V)> {
+    let mut unique: Vec<(K, V)> = vec![];
+
+    for (key, item) in items {
+        if find(&unique, &key).is_none() {
+            unique.push((key, item));
+        }
+    }
+
+    unique
+}
@@ -0,0 +1,244 @@
+//! Contains the [Edit] type, which describes a single change to an [AwsConfigFile] as data, so that a list of
+//! them can be applied to a file all at once with [AwsConfigFile::apply].
+
+use super::{
+    AwsConfigFile, ConfigItem, ConfigPath, NestedSettingPath, SectionName, SectionPath,
+    SettingName, SettingPath, Value, ValueType,
+};
+use crate::Error;
+
+/// A single change to an [AwsConfigFile]. A list of edits is applied with [AwsConfigFile::apply], which
+/// applies either all of them or none of them.
+///
+/// With the `serde` feature, a list of edits can be written in a form which resembles JSON Patch. Each edit
+/// names its operation in the `op` field, and paths are written as strings in the form described in
+/// [ConfigPath]. Values are checked as they are read, so a value which couldn't be read back from the file,
+/// such as one spanning several lines, is rejected before anything is applied:
+///
+/// ```json
+/// [
+///     { "op": "set", "path": "profile.A.region", "value": "us-east-1" },
+///     { "op": "set_nested", "path": "profile.A.s3.max_queue_size", "value": "1000" },
+///     { "op": "remove", "path": "profile.B" },
+///     { "op": "rename", "path": "profile.C", "name": "D" },
+///     { "op": "insert_section", "path": "services.local" }
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "op", rename_all = "snake_case")
+)]
+pub enum Edit {
+    /// Set the [Value] of a [crate::Setting], as with [AwsConfigFile::set]. The setting and its section are
+    /// created if they don't exist.
+    Set {
+        /// The path of the setting
+        path: SettingPath,
+
+        /// The value to give it
+        value: Value,
+    },
+
+    /// Set the [Value] of a [crate::NestedSetting], as with [AwsConfigFile::set_nested]. Fails if the parent
+    /// setting holds a single value.
+    SetNested {
+        /// The path of the nested setting
+        path: NestedSettingPath,
+
+        /// The value to give it
+        value: Value,
+    },
+
+    /// Remove whatever the path points to, as with [AwsConfigFile::remove]. Fails if it doesn't exist.
+    Remove {
+        /// The path of the section, setting or nested setting to remove
+        path: ConfigPath,
+    },
+
+    /// Give a new name to whatever the path points to, keeping its place, its comments and its contents. A
+    /// section keeps its type. Fails if it doesn't exist, if it is a section without a name such as
+    /// `[plugins]`, if the name is invalid, or if there already is an item with that name in the same place.
+    Rename {
+        /// The path of the section, setting or nested setting to rename
+        path: ConfigPath,
+
+        /// The new name, which is a [SectionName] or a [SettingName] depending on the depth of the path
+        name: String,
+    },
+
+    /// Create a [crate::Section] without any settings at the end of the file, unless it already exists
+    InsertSection {
+        /// The path of the section
+        path: SectionPath,
+    },
+}
+
+/// What a single [Edit] changed, as listed in a [Report]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Nothing changed, because the file already looked the way the edit asked for
+    Unchanged,
+
+    /// The item didn't exist and was created, along with its section if that didn't exist either
+    Created,
+
+    /// The value of an existing setting was replaced. This holds the previous value, which may have been a
+    /// block of nested settings.
+    Updated(ValueType),
+
+    /// The item was removed from the file, and this holds it
+    Removed(ConfigItem),
+
+    /// The item was renamed, and this holds the path it had before
+    Renamed(ConfigPath),
+}
+
+/// The outcome of [AwsConfigFile::apply], which lists what each [Edit] changed
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Report {
+    /// What each edit changed, in the same order as the edits
+    pub(crate) changes: Vec<Change>,
+}
+
+impl Report {
+    /// What each [Edit] changed, in the same order as the edits were given
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Whether any of the edits changed the file
+    pub fn has_changes(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|change| *change != Change::Unchanged)
+    }
+}
+
+impl Edit {
+    /// Apply this edit to the given file, and describe what it changed. If it fails, the file may have been
+    /// changed partially, which [AwsConfigFile::apply] guards against.
+    pub(crate) fn apply_to(&self, config: &mut AwsConfigFile) -> Result<Change, Error> {
+        match self {
+            Edit::Set { path, value } => {
+                let previous = config
+                    .get_setting(path)
+                    .map(|setting| setting.value().clone());
+                if matches!(&previous, Some(ValueType::Single(existing)) if existing == value) {
+                    return Ok(Change::Unchanged);
+                }
+
+                config.set(path.clone(), value.clone())?;
+                Ok(previous.map_or(Change::Created, Change::Updated))
+            }
+            Edit::SetNested { path, value } => {
+                let previous = config
+                    .get_nested_setting(path)
+                    .map(|nested| nested.value().clone());
+                if previous.as_ref() == Some(value) {
+                    return Ok(Change::Unchanged);
+                }
+
+                config.set_nested(path.clone(), value.clone())?;
+                Ok(previous.map_or(Change::Created, |previous| {
+                    Change::Updated(ValueType::Single(previous))
+                }))
+            }
+            Edit::Remove { path } => config
+                .remove(path)
+                .map(Change::Removed)
+                .ok_or_else(|| Error::NotFound(path.clone())),
+            Edit::InsertSection { path } => match config.get_section(path) {
+                Some(_) => Ok(Change::Unchanged),
+                None => {
+                    config.insert_section(path);
+                    Ok(Change::Created)
+                }
+            },
+            Edit::Rename { path, name } => rename(config, path, name),
+        }
+    }
+}
+
+/// Give a new name to whatever the [ConfigPath] points to, as described in [Edit::Rename]
+fn rename(config: &mut AwsConfigFile, path: &ConfigPath, name: &str) -> Result<Change, Error> {
+    let not_found = || Error::NotFound(path.clone());
+
+    let renamed = match path {
+        ConfigPath::Section(section_path) => {
+            if section_path.section_name.is_none() {
+                return Err(Error::Unnamed(path.clone()));
+            }
+
+            let section_name = name.parse::<SectionName>()?;
+            let renamed = SectionPath {
+                section_type: section_path.section_type.clone(),
+                section_name: Some(section_name.clone()),
+            };
+            if renamed != *section_path && config.get_section(&renamed).is_some() {
+                return Err(Error::AlreadyExists(renamed.into()));
+            }
+
+            config
+                .get_section_mut(&section_path.section_type, &section_path.section_name)
+                .ok_or_else(not_found)?
+                .header
+                .rename(section_name);
+            ConfigPath::Section(renamed)
+        }
+        ConfigPath::Setting(setting_path) => {
+            let setting_name = name.parse::<SettingName>()?;
+            let renamed = SettingPath {
+                section_path: setting_path.section_path.clone(),
+                setting_name: setting_name.clone(),
+            };
+            if renamed != *setting_path && config.get_setting(&renamed).is_some() {
+                return Err(Error::AlreadyExists(renamed.into()));
+            }
+
+            let SectionPath {
+                section_type,
+                section_name,
+            } = &setting_path.section_path;
+            config
+                .get_section_mut(section_type, section_name)
+                .and_then(|section| section.get_setting_mut(&setting_path.setting_name))
+                .ok_or_else(not_found)?
+                .setting_name = setting_name;
+            ConfigPath::Setting(renamed)
+        }
+        ConfigPath::NestedSetting(nested_path) => {
+            let nested_setting_name = name.parse::<SettingName>()?;
+            let renamed = NestedSettingPath {
+                section_path: nested_path.section_path.clone(),
+                setting_name: nested_path.setting_name.clone(),
+                nested_setting_name: nested_setting_name.clone(),
+            };
+            if renamed != *nested_path && config.get_nested_setting(&renamed).is_some() {
+                return Err(Error::AlreadyExists(renamed.into()));
+            }
+
+            let SectionPath {
+                section_type,
+                section_name,
+            } = &nested_path.section_path;
+            config
+                .get_section_mut(section_type, section_name)
+                .and_then(|section| {
+                    section.get_nested_setting_mut(
+                        &nested_path.setting_name,
+                        &nested_path.nested_setting_name,
+                    )
+                })
+                .ok_or_else(not_found)?
+                .setting_name = nested_setting_name;
+            ConfigPath::NestedSetting(renamed)
+        }
+    };
+
+    match renamed == *path {
+        true => Ok(Change::Unchanged),
+        false => Ok(Change::Renamed(path.clone())),
+    }
+}
@@ -0,0 +1,43 @@
+//! Contains the [EndpointUrl] type, which is the typed value of the `endpoint_url` setting.
+
+use crate::Error;
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+/// The URL of a service endpoint, such as `https://s3.us-east-1.amazonaws.com` or `http://localhost:4566`.
+/// It has to start with `http://` or `https://`, followed by a host, and can't contain whitespace.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EndpointUrl(String);
+
+impl FromStr for EndpointUrl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let host = s
+            .strip_prefix("https://")
+            .or_else(|| s.strip_prefix("http://"));
+        let is_valid = host.is_some_and(|host| !host.is_empty() && !host.starts_with('/'))
+            && !s.contains(char::is_whitespace);
+
+        match is_valid {
+            true => Ok(Self(s.to_string())),
+            false => Err(Error::InvalidValue {
+                value: s.to_string(),
+                expected: "a URL starting with 'http://' or 'https://'",
+            }),
+        }
+    }
+}
+
+impl Display for EndpointUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for EndpointUrl {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,266 @@
+//! Contains the entry types, which make it possible to look up a section or setting once and then either work
+//! with the existing item or create it, in the style of [std::collections::hash_map::Entry].
+
+use super::{
+    header::{ConfigHeader, Header},
+    section::remove_section_at,
+    AwsConfigFile, Section, SectionPath, Setting, SettingName, Value, ValueType,
+};
+
+/// A view into a single [Section] of an [AwsConfigFile], which may or may not exist. It is created by
+/// [AwsConfigFile::section_entry].
+///
+/// ```
+/// # use aws_config_mod::{AwsConfigFile, SectionPath, Value};
+/// let mut config = AwsConfigFile::default();
+/// let section_path = SectionPath::try_from("profile.dev").unwrap();
+///
+/// config.section_entry(section_path).or_insert_with(|section| {
+///     section.set("region".parse().unwrap(), Value::from("us-east-1"));
+///     section.set("output".parse().unwrap(), Value::from("json"));
+/// });
+///
+/// assert_eq!(config.to_string(), "[profile dev]\nregion = us-east-1\noutput = json\n");
+/// ```
+#[derive(Debug)]
+pub enum SectionEntry<'a> {
+    /// The [Section] exists
+    Occupied(OccupiedSectionEntry<'a>),
+
+    /// The [Section] doesn't exist yet
+    Vacant(VacantSectionEntry<'a>),
+}
+
+impl<'a> SectionEntry<'a> {
+    /// The [SectionPath] this entry was created for
+    pub fn path(&self) -> &SectionPath {
+        match self {
+            SectionEntry::Occupied(entry) => entry.path(),
+            SectionEntry::Vacant(entry) => entry.path(),
+        }
+    }
+
+    /// Return the [Section], creating it without any settings if it doesn't exist
+    pub fn or_insert(self) -> &'a mut Section<ConfigHeader> {
+        self.or_insert_with(|_| ())
+    }
+
+    /// Return the [Section]. If it doesn't exist, it is created and handed to `default` first, which can fill
+    /// it with settings.
+    pub fn or_insert_with(
+        self,
+        default: impl FnOnce(&mut Section<ConfigHeader>),
+    ) -> &'a mut Section<ConfigHeader> {
+        match self {
+            SectionEntry::Occupied(entry) => entry.into_mut(),
+            SectionEntry::Vacant(entry) => {
+                let section = entry.insert();
+                default(section);
+                section
+            }
+        }
+    }
+
+    /// Change the [Section] if it exists, and return the entry so that it can be inserted otherwise
+    pub fn and_modify(mut self, modify: impl FnOnce(&mut Section<ConfigHeader>)) -> Self {
+        if let SectionEntry::Occupied(entry) = &mut self {
+            modify(entry.get_mut())
+        }
+
+        self
+    }
+}
+
+/// A view into a [Section] which exists, as part of a [SectionEntry]
+#[derive(Debug)]
+pub struct OccupiedSectionEntry<'a> {
+    /// The sections of the file
+    pub(crate) sections: &'a mut Vec<Section<ConfigHeader>>,
+
+    /// The position of the [Section] within the file
+    pub(crate) index: usize,
+
+    /// The [SectionPath] this entry was created for
+    pub(crate) section_path: SectionPath,
+}
+
+impl<'a> OccupiedSectionEntry<'a> {
+    /// The [SectionPath] this entry was created for
+    pub fn path(&self) -> &SectionPath {
+        &self.section_path
+    }
+
+    /// Get an immutable reference to the [Section]
+    pub fn get(&self) -> &Section<ConfigHeader> {
+        &self.sections[self.index]
+    }
+
+    /// Get a mutable reference to the [Section]
+    pub fn get_mut(&mut self) -> &mut Section<ConfigHeader> {
+        &mut self.sections[self.index]
+    }
+
+    /// Turn the entry into a mutable reference to the [Section] which lives as long as the file is borrowed
+    pub fn into_mut(self) -> &'a mut Section<ConfigHeader> {
+        &mut self.sections[self.index]
+    }
+
+    /// Remove the [Section] from the file, as described in [AwsConfigFile#comments-and-blank-lines]
+    pub fn remove(self) -> Section<ConfigHeader> {
+        remove_section_at(self.sections, self.index)
+    }
+}
+
+/// A view into a [Section] which doesn't exist yet, as part of a [SectionEntry]
+#[derive(Debug)]
+pub struct VacantSectionEntry<'a> {
+    /// The file that the [Section] would be added to
+    pub(crate) config: &'a mut AwsConfigFile,
+
+    /// The [SectionPath] this entry was created for
+    pub(crate) section_path: SectionPath,
+}
+
+impl<'a> VacantSectionEntry<'a> {
+    /// The [SectionPath] this entry was created for
+    pub fn path(&self) -> &SectionPath {
+        &self.section_path
+    }
+
+    /// Add a [Section] without any settings to the end of the file and return a mutable reference to it
+    pub fn insert(self) -> &'a mut Section<ConfigHeader> {
+        self.config.push_section(self.section_path)
+    }
+}
+
+/// A view into a single [Setting] of a [Section], which may or may not exist. It is created by [Section::entry].
+///
+/// ```
+/// # use aws_config_mod::{AwsConfigFile, SectionPath, Value};
+/// let mut config: AwsConfigFile = "[profile dev]\nregion = us-east-1\n".parse().unwrap();
+/// let section_path = SectionPath::try_from("profile.dev").unwrap();
+/// let section = config.section_entry(section_path).or_insert();
+///
+/// section
+///     .entry("region".parse().unwrap())
+///     .and_modify(|setting| setting.set(Value::from("eu-west-1")))
+///     .or_insert(Value::from("us-west-2"));
+/// section.entry("output".parse().unwrap()).or_insert(Value::from("json"));
+///
+/// assert_eq!(config.to_string(), "[profile dev]\nregion = eu-west-1\noutput = json\n");
+/// ```
+#[derive(Debug)]
+pub enum SettingEntry<'a, T> {
+    /// The [Setting] exists
+    Occupied(OccupiedSettingEntry<'a, T>),
+
+    /// The [Setting] doesn't exist yet
+    Vacant(VacantSettingEntry<'a, T>),
+}
+
+impl<'a, T> SettingEntry<'a, T>
+where
+    T: Header,
+{
+    /// The [SettingName] this entry was created for
+    pub fn name(&self) -> &SettingName {
+        match self {
+            SettingEntry::Occupied(entry) => entry.name(),
+            SettingEntry::Vacant(entry) => entry.name(),
+        }
+    }
+
+    /// Return the [Setting], creating it with the given [Value] if it doesn't exist
+    pub fn or_insert(self, value: Value) -> &'a mut Setting {
+        self.or_insert_with(|| value)
+    }
+
+    /// Return the [Setting], creating it with the [Value] returned by `default` if it doesn't exist
+    pub fn or_insert_with(self, default: impl FnOnce() -> Value) -> &'a mut Setting {
+        match self {
+            SettingEntry::Occupied(entry) => entry.into_mut(),
+            SettingEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Change the [Setting] if it exists, and return the entry so that it can be inserted otherwise
+    pub fn and_modify(mut self, modify: impl FnOnce(&mut Setting)) -> Self {
+        if let SettingEntry::Occupied(entry) = &mut self {
+            modify(entry.get_mut())
+        }
+
+        self
+    }
+}
+
+/// A view into a [Setting] which exists, as part of a [SettingEntry]
+#[derive(Debug)]
+pub struct OccupiedSettingEntry<'a, T> {
+    /// The section containing the [Setting]
+    pub(crate) section: &'a mut Section<T>,
+
+    /// The position of the [Setting] within the section
+    pub(crate) index: usize,
+}
+
+impl<'a, T> OccupiedSettingEntry<'a, T>
+where
+    T: Header,
+{
+    /// The [SettingName] this entry was created for
+    pub fn name(&self) -> &SettingName {
+        self.get().name()
+    }
+
+    /// Get an immutable reference to the [Setting]
+    pub fn get(&self) -> &Setting {
+        &self.section.settings[self.index]
+    }
+
+    /// Get a mutable reference to the [Setting]
+    pub fn get_mut(&mut self) -> &mut Setting {
+        &mut self.section.settings[self.index]
+    }
+
+    /// Turn the entry into a mutable reference to the [Setting] which lives as long as the section is borrowed
+    pub fn into_mut(self) -> &'a mut Setting {
+        &mut self.section.settings[self.index]
+    }
+
+    /// Remove the [Setting] from its section, as described in [AwsConfigFile#comments-and-blank-lines]
+    pub fn remove(self) -> Setting {
+        let setting_name = self.name().clone();
+
+        #[allow(clippy::unwrap_used)]
+        // This cannot fail because an occupied entry always points to an existing setting
+        self.section.remove_setting(&setting_name).unwrap()
+    }
+}
+
+/// A view into a [Setting] which doesn't exist yet, as part of a [SettingEntry]
+#[derive(Debug)]
+pub struct VacantSettingEntry<'a, T> {
+    /// The section that the [Setting] would be added to
+    pub(crate) section: &'a mut Section<T>,
+
+    /// The [SettingName] this entry was created for
+    pub(crate) setting_name: SettingName,
+}
+
+impl<'a, T> VacantSettingEntry<'a, T>
+where
+    T: Header,
+{
+    /// The [SettingName] this entry was created for
+    pub fn name(&self) -> &SettingName {
+        &self.setting_name
+    }
+
+    /// Add a [Setting] with the given [Value] to the end of the section and return a mutable reference to it
+    pub fn insert(self, value: Value) -> &'a mut Setting {
+        let value = ValueType::Single(value);
+        let setting = Setting::with_style(self.setting_name, value, &self.section.style);
+
+        self.section.push_setting(setting)
+    }
+}
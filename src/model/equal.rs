@@ -1,8 +1,9 @@
 //! Items related to how '=' signs are parsed and stringified. This type is internal and
 //! should not be exposed directly to end users.
 
+use crate::error::Expected;
 use crate::lexer::{equal, Parsable, ParserOutput};
-use nom::{character::complete::space0, combinator::recognize, Parser};
+use nom::{character::complete::space0, combinator::recognize, error::context, Parser};
 use std::{fmt::Display, ops::Deref};
 
 /// Represents an equal sign and it's surrounding whitespace.
@@ -20,6 +21,12 @@ impl Equal {
 
         Self(inner)
     }
+
+    /// The same [Equal] without any padding after the '=' sign, for a setting which is followed by a block of
+    /// nested settings rather than a value
+    pub fn without_trailing_padding(&self) -> Self {
+        Self(self.0.trim_end().to_string())
+    }
 }
 
 impl Default for Equal {
@@ -46,9 +53,12 @@ impl<'a> Parsable<'a> for Equal {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        recognize(space0.and(equal).and(space0))
-            .map(str::to_string)
-            .map(Self)
-            .parse(input)
+        context(
+            Expected::Equal.label(),
+            recognize(space0.and(equal).and(space0)),
+        )
+        .map(str::to_string)
+        .map(Self)
+        .parse(input)
     }
 }
@@ -0,0 +1,93 @@
+//! Contains the options of the formatter, which rewrites the spacing, indentation and blank lines of a file
+//! into one consistent style.
+
+use super::{equal::Equal, indent::Indent, style::Style, LineEnding};
+
+/// Describes the style that [crate::AwsConfigFile::format] and [crate::AwsCredentialsFile::format] rewrite a
+/// file into. Formatting only changes spacing, indentation, blank lines and line endings, and the order of
+/// items if sorting is enabled. The text of comments and values is never changed.
+///
+/// ```
+/// # use aws_config_mod::{AwsConfigFile, FormatOptions};
+/// let mut config: AwsConfigFile = "[profile B]\nregion=us-west-2   # oregon\n[profile A]\n\n\nregion   =   us-east-1"
+///     .parse()
+///     .unwrap();
+///
+/// config.format(&FormatOptions {
+///     sort_sections: true,
+///     ..Default::default()
+/// });
+///
+/// assert_eq!(
+///     config.to_string(),
+///     "[profile A]\nregion = us-east-1\n\n[profile B]\nregion = us-west-2 # oregon\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormatOptions {
+    /// The number of spaces on each side of the '=' sign of a setting
+    pub equal_padding: usize,
+
+    /// The number of blank lines between two sections, and between the comments at the head of the file and
+    /// the first section
+    pub blank_lines_between_sections: usize,
+
+    /// The number of spaces that nested settings are indented by. Nested settings have to be indented, so
+    /// zero is treated as one.
+    pub nested_indent: usize,
+
+    /// Whether the file ends with a line break. If it doesn't, the line break at the end of the last line is
+    /// removed.
+    pub trailing_newline: bool,
+
+    /// The number of spaces between the content of a line and a comment after it on the same line. A '#'
+    /// directly after a value would become part of the value, so zero is treated as one.
+    pub comment_spacing: usize,
+
+    /// Whether to sort sections by their [crate::SectionType], and sections of the same type by name
+    pub sort_sections: bool,
+
+    /// Whether to sort the settings of each section, and the nested settings of each setting, by name
+    pub sort_settings: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            equal_padding: 1,
+            blank_lines_between_sections: 1,
+            nested_indent: 2,
+            trailing_newline: true,
+            comment_spacing: 1,
+            sort_sections: false,
+            sort_settings: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// The indentation of nested settings
+    pub(crate) fn indent(&self) -> Indent {
+        Indent::spaces(self.nested_indent.max(1))
+    }
+
+    /// The '=' sign of a setting with a single value
+    pub(crate) fn equal(&self) -> Equal {
+        Equal::padded(self.equal_padding)
+    }
+
+    /// The number of spaces in front of a comment at the end of a line
+    pub(crate) fn comment_spacing(&self) -> usize {
+        self.comment_spacing.max(1)
+    }
+
+    /// The [Style] in which settings and sections are added to a file after it has been formatted
+    pub(crate) fn style(&self, line_ending: LineEnding) -> Style {
+        Style {
+            line_ending,
+            equal: self.equal(),
+            blank_lines_between_sections: self.blank_lines_between_sections,
+            nested_indent: self.indent(),
+        }
+    }
+}
@@ -3,17 +3,53 @@
 //! [default] and [profile A] are both valid headers
 
 use super::{
-    section_name::SectionName, section_type::SectionType, whitespace::Whitespace, SectionPath,
+    section_name::SectionName,
+    section_type::SectionType,
+    whitespace::{self, Whitespace},
+    SectionPath,
 };
+use crate::error::Expected;
 use crate::lexer::{Parsable, ParserOutput};
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    combinator::map,
-    sequence::{delimited, separated_pair},
+    combinator::{map, peek},
+    error::context,
+    sequence::{delimited, separated_pair, terminated},
 };
 use std::fmt::Display;
 
+/// The header of a [crate::Section], implemented by [ConfigHeader] and [CredentialHeader], so that code can work
+/// with the sections of either kind of file. The header is written out through its [Display] implementation,
+/// exactly as it appears in the file, including anything that follows it on the same line.
+///
+/// This trait cannot be implemented outside of this crate.
+// The private supertrait is what seals this trait
+#[allow(private_bounds)]
+pub trait Header: Default + Display + HeaderLine {
+    /// The [SectionType] of the section. For example, in `[profile A]` this is [SectionType::Profile].
+    fn section_type(&self) -> &SectionType;
+
+    /// The [SectionName] of the section, if it has one. For example, in `[profile A]` this is 'A', while
+    /// `[plugins]` has no name.
+    fn section_name(&self) -> Option<&SectionName>;
+
+    /// The whitespace and comment which follow the header on the same line, including the line ending
+    fn trailing_whitespace(&self) -> &str;
+
+    /// The text of the comment after the header on the same line, without the '#' which starts it
+    fn inline_comment(&self) -> Option<&str> {
+        whitespace::inline_comment(self.trailing_whitespace())
+    }
+}
+
+/// Lets code which is generic over both kinds of header work with the line the header is on. This trait is
+/// private to this crate, so it cannot be implemented or called outside of it.
+pub(crate) trait HeaderLine {
+    /// The whitespace and comment which follow the header on the same line, including the line ending
+    fn line_end_mut(&mut self) -> &mut Whitespace;
+}
+
 /// A header of a config section. Contains the section type as well as the profile.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
 pub struct ConfigHeader {
@@ -23,18 +59,40 @@ pub struct ConfigHeader {
     /// The section type. For example, in [profile A], 'profile' is the section type.
     pub(crate) section_type: SectionType,
 
-    /// Any whitespace or comment which follows the header
+    /// Any whitespace or comment which follows the header on the same line, including the line ending
     pub(crate) whitespace: Whitespace,
+
+    /// Whether the header of the default profile is written in the short form, `[default]`, rather than as
+    /// `[profile default]`
+    pub(crate) bare_default: bool,
 }
 
 impl ConfigHeader {
-    /// Provided a [SectionType] and optional [SectionName], creates a new section [Header].
+    /// Provided a [SectionType] and optional [SectionName], creates a new section [Header]. The default profile
+    /// gets the short header `[default]`.
     pub fn new(section_type: SectionType, section_name: Option<SectionName>) -> Self {
-        Self {
+        let mut header = Self {
             section_name,
             section_type,
             whitespace: Default::default(),
-        }
+            bare_default: false,
+        };
+        header.bare_default = header.is_default_profile();
+
+        header
+    }
+
+    /// Give the section a new name, keeping its type. A profile renamed to `default` gets the short header
+    /// `[default]`, like a new default profile, and the default profile gets the long form if it is renamed.
+    pub(crate) fn rename(&mut self, section_name: SectionName) {
+        self.section_name = Some(section_name);
+        self.bare_default = self.is_default_profile();
+    }
+
+    /// Indicates whether this is the header of the default profile written as `[default]`, as opposed to
+    /// `[profile default]`
+    pub fn is_bare_default(&self) -> bool {
+        self.bare_default
     }
 
     /// Indicates whether this header belongs to the default profile
@@ -53,10 +111,30 @@ impl From<SectionPath> for ConfigHeader {
     }
 }
 
+impl Header for ConfigHeader {
+    fn section_type(&self) -> &SectionType {
+        &self.section_type
+    }
+
+    fn section_name(&self) -> Option<&SectionName> {
+        self.section_name.as_ref()
+    }
+
+    fn trailing_whitespace(&self) -> &str {
+        &self.whitespace
+    }
+}
+
+impl HeaderLine for ConfigHeader {
+    fn line_end_mut(&mut self) -> &mut Whitespace {
+        &mut self.whitespace
+    }
+}
+
 impl Display for ConfigHeader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(section_name) = &self.section_name {
-            if self.is_default_profile() {
+            if self.bare_default {
                 write!(f, "[{}]{}", section_name, self.whitespace)
             } else {
                 write!(
@@ -75,27 +153,38 @@ impl<'a> Parsable<'a> for ConfigHeader {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (next, (section_name, section_type)) = delimited(
-            tag("["),
-            alt((
-                map(tag("default"), |default: &str| {
-                    (Some(SectionName(default.to_string())), SectionType::Profile)
-                }),
-                map(
-                    separated_pair(SectionType::parse, tag(" "), SectionName::parse),
-                    |(section_type, section_name)| (Some(section_name), section_type),
-                ),
-                map(SectionType::parse, |section_type| (None, section_type)),
-            )),
-            tag("]"),
+        let (next, (section_name, section_type, bare_default)) = context(
+            Expected::Header.label(),
+            delimited(
+                tag("["),
+                alt((
+                    map(
+                        terminated(tag("default"), peek(tag("]"))),
+                        |default: &str| {
+                            let section_name = SectionName(default.to_string());
+                            (Some(section_name), SectionType::Profile, true)
+                        },
+                    ),
+                    map(
+                        separated_pair(SectionType::parse, tag(" "), SectionName::parse),
+                        |(section_type, section_name)| (Some(section_name), section_type, false),
+                    ),
+                    map(SectionType::parse, |section_type| {
+                        (None, section_type, false)
+                    }),
+                )),
+                tag("]"),
+            ),
         )(input)?;
 
-        let (next, whitespace) = Whitespace::parse(next)?;
+        let (next, whitespace) =
+            context(Expected::Header.label(), Whitespace::parse_line_end)(next)?;
 
         let header = Self {
             section_name,
             section_type,
             whitespace,
+            bare_default,
         };
 
         Ok((next, header))
@@ -114,7 +203,7 @@ pub struct CredentialHeader {
     /// entry in the credentials file is just [A]
     pub(crate) profile_name: SectionName,
 
-    /// Any whitespace or comment which follows the header
+    /// Any whitespace or comment which follows the header on the same line, including the line ending
     pub(crate) whitespace: Whitespace,
 }
 
@@ -138,9 +227,29 @@ impl CredentialHeader {
     }
 }
 
+impl Header for CredentialHeader {
+    fn section_type(&self) -> &SectionType {
+        self.get_type()
+    }
+
+    fn section_name(&self) -> Option<&SectionName> {
+        Some(self.get_name())
+    }
+
+    fn trailing_whitespace(&self) -> &str {
+        &self.whitespace
+    }
+}
+
+impl HeaderLine for CredentialHeader {
+    fn line_end_mut(&mut self) -> &mut Whitespace {
+        &mut self.whitespace
+    }
+}
+
 impl Display for CredentialHeader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}]", self.profile_name)
+        write!(f, "[{}]{}", self.profile_name, self.whitespace)
     }
 }
 
@@ -148,9 +257,13 @@ impl<'a> Parsable<'a> for CredentialHeader {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (next, profile_name) = delimited(tag("["), SectionName::parse, tag("]"))(input)?;
+        let (next, profile_name) = context(
+            Expected::Header.label(),
+            delimited(tag("["), SectionName::parse, tag("]")),
+        )(input)?;
 
-        let (next, whitespace) = Whitespace::parse(next)?;
+        let (next, whitespace) =
+            context(Expected::Header.label(), Whitespace::parse_line_end)(next)?;
 
         let header = Self {
             profile_name,
@@ -160,3 +273,32 @@ impl<'a> Parsable<'a> for CredentialHeader {
         Ok((next, header))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ConfigHeader;
+    use crate::{lexer::Parsable, SectionType};
+
+    #[test]
+    fn both_forms_of_the_default_profile_round_trip() {
+        for input in ["[default]\n", "[profile default] # comment\n"] {
+            let (rest, header) = ConfigHeader::parse(input).expect("Should be valid");
+
+            assert!(rest.is_empty());
+            assert!(header.is_default_profile());
+            assert_eq!(header.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn names_starting_with_default_are_not_the_default_profile() {
+        let (_, header) = ConfigHeader::parse("[defaultfoo]\n").expect("Should be valid");
+
+        assert!(!header.is_default_profile());
+        assert_eq!(
+            header.section_type,
+            SectionType::Other("defaultfoo".to_string())
+        );
+        assert_eq!(header.to_string(), "[defaultfoo]\n");
+    }
+}
@@ -15,6 +15,18 @@ use std::fmt::Display;
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Default)]
 pub(crate) struct Indent(String);
 
+impl Indent {
+    /// Whether there is no indentation at all
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// An indentation of the given number of spaces
+    pub(crate) fn spaces(count: usize) -> Self {
+        Self(" ".repeat(count))
+    }
+}
+
 impl PartialEq<&str> for Indent {
     fn eq(&self, other: &&str) -> bool {
         self.0.as_str() == *other
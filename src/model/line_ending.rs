@@ -0,0 +1,66 @@
+//! Contains items related to the line endings used by a file. Files written on Windows usually end their lines
+//! with `\r\n` rather than `\n`, and any lines added to such a file should do the same.
+
+use std::fmt::Display;
+
+/// The character sequence that ends each line of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub enum LineEnding {
+    /// A single line feed, `\n`, as used on Linux and macOS
+    #[default]
+    Lf,
+
+    /// A carriage return followed by a line feed, `\r\n`, as used on Windows
+    CrLf,
+}
+
+impl LineEnding {
+    /// Find the line ending used by most of the lines in `input`. Falls back to [LineEnding::Lf] if there
+    /// are no line endings at all, or in the event of a tie.
+    pub fn detect(input: &str) -> Self {
+        let crlf = input.matches("\r\n").count();
+        let lf = input.matches('\n').count() - crlf;
+
+        if crlf > lf {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+
+    /// The character sequence of this line ending
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LineEnding;
+
+    #[test]
+    fn detects_the_dominant_line_ending() {
+        assert_eq!(
+            LineEnding::detect("[default]\r\nregion=us-west-2\r\n"),
+            LineEnding::CrLf
+        );
+        assert_eq!(
+            LineEnding::detect("[default]\nregion=us-west-2\r\n"),
+            LineEnding::Lf
+        );
+        assert_eq!(
+            LineEnding::detect("[default]\r\na=b\r\nc=d\n"),
+            LineEnding::CrLf
+        );
+        assert_eq!(LineEnding::detect("[default]"), LineEnding::Lf);
+    }
+}
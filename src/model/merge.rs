@@ -0,0 +1,547 @@
+//! Contains the three-way merge of config files, as done by [AwsConfigFile::merge]. The merge works on the
+//! sections, settings and nested settings of the files rather than on their lines, so changes which only touch
+//! different items never conflict, however close together they are.
+
+use super::{
+    diff::{
+        diff_nested_setting, diff_section, diff_setting, find, keys, nested_settings_by_name,
+        sections_by_path, settings_by_name,
+    },
+    header::{ConfigHeader, CredentialHeader, Header},
+    nested_settings::NestedSettings,
+    AwsConfigFile, AwsCredentialsFile, ConfigPath, Difference, NestedSettingPath, Section,
+    SectionPath, Setting, SettingPath, ValueType,
+};
+use std::fmt::Display;
+
+/// The outcome of [AwsConfigFile::merge]: the merged file, along with the conflicts which couldn't be merged.
+/// Wherever there is a conflict, the merged file holds our side of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeResult {
+    /// The merged file
+    pub(crate) merged: AwsConfigFile,
+
+    /// The items which both sides changed in different ways
+    pub(crate) conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    /// The merged file, which holds our side of each conflict
+    pub fn merged(&self) -> &AwsConfigFile {
+        &self.merged
+    }
+
+    /// Turn the result into the merged file, which holds our side of each conflict
+    pub fn into_merged(self) -> AwsConfigFile {
+        self.merged
+    }
+
+    /// The items which both sides changed in different ways, in the order they appear in our file
+    pub fn conflicts(&self) -> &[MergeConflict] {
+        &self.conflicts
+    }
+
+    /// Whether the merge succeeded without any conflicts
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// An item which both sides of a merge changed in different ways. Each side is described by the differences
+/// between the base file and that side, at the item or within it. As in a [crate::ConfigDiff], the values of
+/// settings which hold secrets are masked.
+///
+/// The [Display] implementation renders the conflict over several lines:
+///
+/// ```text
+/// conflict at profile.B.region
+///   ours:   profile B: region changed from us-east-1 to eu-west-1
+///   theirs: profile B: region removed
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// The path of the item which both sides changed
+    pub(crate) path: ConfigPath,
+
+    /// The changes which our side made
+    pub(crate) ours: Vec<Difference>,
+
+    /// The changes which their side made
+    pub(crate) theirs: Vec<Difference>,
+}
+
+impl MergeConflict {
+    /// Describe a conflict at the given path, masking the values of secrets
+    fn new(path: impl Into<ConfigPath>, ours: Vec<Difference>, theirs: Vec<Difference>) -> Self {
+        Self {
+            path: path.into(),
+            ours: ours.into_iter().map(Difference::masked).collect(),
+            theirs: theirs.into_iter().map(Difference::masked).collect(),
+        }
+    }
+
+    /// The path of the item which both sides changed
+    pub fn path(&self) -> &ConfigPath {
+        &self.path
+    }
+
+    /// The changes which our side made, compared to the base file
+    pub fn ours(&self) -> &[Difference] {
+        &self.ours
+    }
+
+    /// The changes which their side made, compared to the base file
+    pub fn theirs(&self) -> &[Difference] {
+        &self.theirs
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "conflict at {}", self.path)?;
+        self.ours
+            .iter()
+            .try_for_each(|difference| write!(f, "\n  ours:   {difference}"))?;
+        self.theirs
+            .iter()
+            .try_for_each(|difference| write!(f, "\n  theirs: {difference}"))
+    }
+}
+
+/// The parts of a file which a merge works on, so that both kinds of file can be merged the same way
+pub(crate) trait MergeFile: Clone {
+    /// The kind of header of the sections in the file
+    type Header: Header + Clone;
+
+    /// The sections which can be found by their path, in the order they appear
+    fn mergeable_sections(&self) -> impl Iterator<Item = &Section<Self::Header>>;
+
+    /// The comment lines at the head of the file
+    fn head_comments(&self) -> Vec<&str>;
+
+    /// Replace the comment lines at the head of the file
+    fn set_head_comments(&mut self, comments: Vec<&str>);
+
+    /// The comment lines at the end of the file
+    fn tail_comments(&self) -> Vec<&str>;
+
+    /// Replace the comment lines at the end of the file
+    fn set_tail_comments(&mut self, comments: Vec<&str>);
+
+    /// Find the position of the section with the given path
+    fn position_of(&self, section_path: &SectionPath) -> Option<usize>;
+
+    /// Get a mutable reference to the section at the given position
+    fn section_at_mut(&mut self, index: usize) -> &mut Section<Self::Header>;
+
+    /// Remove the section with the given path, along with the lines it owns
+    fn remove_at_path(&mut self, section_path: &SectionPath);
+
+    /// Put a section from another file at the given position, keeping its comments
+    fn place(&mut self, index: usize, section: Section<Self::Header>);
+}
+
+impl MergeFile for AwsConfigFile {
+    type Header = ConfigHeader;
+
+    fn mergeable_sections(&self) -> impl Iterator<Item = &Section<ConfigHeader>> {
+        self.visible_sections()
+    }
+
+    fn head_comments(&self) -> Vec<&str> {
+        self.leading_comments().collect()
+    }
+
+    fn set_head_comments(&mut self, comments: Vec<&str>) {
+        self.set_leading_comments(comments)
+    }
+
+    fn tail_comments(&self) -> Vec<&str> {
+        self.trailing_comments().collect()
+    }
+
+    fn set_tail_comments(&mut self, comments: Vec<&str>) {
+        self.set_trailing_comments(comments)
+    }
+
+    fn position_of(&self, section_path: &SectionPath) -> Option<usize> {
+        self.section_index(
+            &section_path.section_type,
+            section_path.section_name.as_ref(),
+        )
+    }
+
+    fn section_at_mut(&mut self, index: usize) -> &mut Section<ConfigHeader> {
+        &mut self.sections[index]
+    }
+
+    fn remove_at_path(&mut self, section_path: &SectionPath) {
+        self.remove_section(section_path);
+    }
+
+    fn place(&mut self, index: usize, section: Section<ConfigHeader>) {
+        self.place_section(index, section);
+    }
+}
+
+impl MergeFile for AwsCredentialsFile {
+    type Header = CredentialHeader;
+
+    fn mergeable_sections(&self) -> impl Iterator<Item = &Section<CredentialHeader>> {
+        self.profiles()
+    }
+
+    fn head_comments(&self) -> Vec<&str> {
+        self.leading_comments().collect()
+    }
+
+    fn set_head_comments(&mut self, comments: Vec<&str>) {
+        self.set_leading_comments(comments)
+    }
+
+    fn tail_comments(&self) -> Vec<&str> {
+        self.trailing_comments().collect()
+    }
+
+    fn set_tail_comments(&mut self, comments: Vec<&str>) {
+        self.set_trailing_comments(comments)
+    }
+
+    fn position_of(&self, section_path: &SectionPath) -> Option<usize> {
+        let profile_name = section_path.section_name.as_ref()?;
+
+        self.profiles
+            .iter()
+            .position(|profile| profile.header.get_name() == profile_name)
+    }
+
+    fn section_at_mut(&mut self, index: usize) -> &mut Section<CredentialHeader> {
+        &mut self.profiles[index]
+    }
+
+    fn remove_at_path(&mut self, section_path: &SectionPath) {
+        if let Some(profile_name) = &section_path.section_name {
+            self.remove_profile(profile_name);
+        }
+    }
+
+    fn place(&mut self, index: usize, section: Section<CredentialHeader>) {
+        self.place_profile(index, section);
+    }
+}
+
+/// Whether a merge takes over the comments which their side changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Comments {
+    /// Take over the comments which only their side changed, as with the values
+    Merge,
+
+    /// Keep our comments everywhere, such as when their side was built from data and has none
+    KeepOurs,
+}
+
+/// Merge the changes from `base` to `theirs` into `ours`, as described in [AwsConfigFile::merge]
+pub(crate) fn merge(
+    base: &AwsConfigFile,
+    ours: &AwsConfigFile,
+    theirs: &AwsConfigFile,
+) -> MergeResult {
+    let mut merged = ours.clone();
+    let conflicts = merge_into(base, ours, theirs, &mut merged, Comments::Merge);
+
+    MergeResult { merged, conflicts }
+}
+
+/// Merge the changes from `base` to `theirs` into `merged`, which starts out as a copy of `ours`, and return
+/// the conflicts
+pub(crate) fn merge_into<F: MergeFile>(
+    base: &F,
+    ours: &F,
+    theirs: &F,
+    merged: &mut F,
+    comments: Comments,
+) -> Vec<MergeConflict> {
+    let mut merger = Merger {
+        comments,
+        conflicts: vec![],
+    };
+
+    if let Some(comments) = merger.merge_comments(
+        base.head_comments(),
+        ours.head_comments(),
+        theirs.head_comments(),
+    ) {
+        merged.set_head_comments(comments);
+    }
+    if let Some(comments) = merger.merge_comments(
+        base.tail_comments(),
+        ours.tail_comments(),
+        theirs.tail_comments(),
+    ) {
+        merged.set_tail_comments(comments);
+    }
+
+    let base_sections = sections_by_path(base.mergeable_sections());
+    let our_sections = sections_by_path(ours.mergeable_sections());
+    let their_sections = sections_by_path(theirs.mergeable_sections());
+
+    for path in keys(&[&our_sections, &their_sections, &base_sections]) {
+        let base_section = find(&base_sections, path);
+        let our_section = find(&our_sections, path);
+        let their_section = find(&their_sections, path);
+
+        let their_changes = diff_section(path, base_section, their_section);
+        let our_changes = diff_section(path, base_section, our_section);
+
+        match (our_section, their_section) {
+            (Some(our_section), Some(their_section)) => {
+                if let Some(index) = merged.position_of(path) {
+                    let sides = Sides {
+                        base: base_section,
+                        ours: our_section,
+                        theirs: their_section,
+                    };
+                    merger.merge_section(path, sides, merged.section_at_mut(index));
+                }
+            }
+            (Some(_), None) if our_changes.is_empty() => {
+                merged.remove_at_path(path);
+            }
+            (None, Some(their_section)) if base_section.is_none() => {
+                let index = their_sections
+                    .iter()
+                    .map(|(preceding, _)| preceding)
+                    .take_while(|preceding| *preceding != path)
+                    .filter_map(|preceding| merged.position_of(preceding))
+                    .last()
+                    .map_or(0, |index| index + 1);
+
+                merged.place(index, their_section.clone());
+            }
+            (None, None) => {}
+            _ if their_changes.is_empty() => {}
+            _ => merger.conflict(path.clone(), our_changes, their_changes),
+        }
+    }
+
+    merger.conflicts
+}
+
+/// The three versions of an item which exists on both sides of a merge. The base file may not have it, if
+/// both sides added it.
+#[derive(Debug, Clone, Copy)]
+struct Sides<'a, T> {
+    /// The item in the base file, if it has it
+    base: Option<&'a T>,
+
+    /// The item on our side
+    ours: &'a T,
+
+    /// The item on their side
+    theirs: &'a T,
+}
+
+/// The state of a merge which is in progress
+#[derive(Debug)]
+struct Merger {
+    /// Whether to take over the comments which their side changed
+    comments: Comments,
+
+    /// The conflicts found so far
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    /// Merge a section which exists on both sides into its copy in the merged file
+    fn merge_section<T: Header>(
+        &mut self,
+        section_path: &SectionPath,
+        sides: Sides<'_, Section<T>>,
+        merged: &mut Section<T>,
+    ) {
+        if let Some(comments) = self.merge_comments(
+            sides
+                .base
+                .map(|base| base.leading_comments().collect())
+                .unwrap_or_default(),
+            sides.ours.leading_comments().collect::<Vec<_>>(),
+            sides.theirs.leading_comments().collect(),
+        ) {
+            merged.set_leading_comments(comments);
+        }
+        if let Some(comment) = self.merge_comments(
+            sides.base.and_then(|base| base.inline_comment()),
+            sides.ours.inline_comment(),
+            sides.theirs.inline_comment(),
+        ) {
+            merged.set_inline_comment(comment);
+        }
+
+        let base_settings = sides.base.map(settings_by_name).unwrap_or_default();
+        let our_settings = settings_by_name(sides.ours);
+        let their_settings = settings_by_name(sides.theirs);
+
+        for setting_name in keys(&[&our_settings, &their_settings, &base_settings]) {
+            let path = SettingPath {
+                section_path: section_path.clone(),
+                setting_name: (*setting_name).clone(),
+            };
+            let base_setting = find(&base_settings, setting_name);
+            let our_setting = find(&our_settings, setting_name);
+            let their_setting = find(&their_settings, setting_name);
+
+            let their_changes = diff_setting(&path, base_setting, their_setting);
+            let our_changes = diff_setting(&path, base_setting, our_setting);
+
+            match (our_setting, their_setting) {
+                (Some(our_setting), Some(their_setting)) => {
+                    let sides = Sides {
+                        base: base_setting,
+                        ours: our_setting,
+                        theirs: their_setting,
+                    };
+                    self.merge_setting(&path, sides, our_changes, their_changes, merged);
+                }
+                (Some(_), None) if our_changes.is_empty() => {
+                    merged.remove_setting(setting_name);
+                }
+                (None, Some(their_setting)) if base_setting.is_none() => {
+                    let index = their_settings
+                        .iter()
+                        .map(|(preceding, _)| preceding)
+                        .take_while(|preceding| *preceding != setting_name)
+                        .filter_map(|preceding| merged.setting_index(preceding))
+                        .last()
+                        .map_or(0, |index| index + 1);
+
+                    merged.place_setting(index, their_setting.clone());
+                }
+                (None, None) => {}
+                _ if their_changes.is_empty() => {}
+                _ => self.conflict(path, our_changes, their_changes),
+            }
+        }
+    }
+
+    /// Merge a setting which exists on both sides into its copy in the merged section. If it holds a block of
+    /// nested settings on both sides, the nested settings are merged one by one.
+    fn merge_setting<T: Header>(
+        &mut self,
+        path: &SettingPath,
+        sides: Sides<'_, Setting>,
+        our_changes: Vec<Difference>,
+        their_changes: Vec<Difference>,
+        merged: &mut Section<T>,
+    ) {
+        let line_ending = merged.line_ending();
+
+        if let Some(merged_setting) = merged.get_setting_mut(&path.setting_name) {
+            if let Some(comments) = self.merge_comments(
+                sides
+                    .base
+                    .map(|base| base.leading_comments().collect())
+                    .unwrap_or_default(),
+                sides.ours.leading_comments().collect::<Vec<_>>(),
+                sides.theirs.leading_comments().collect(),
+            ) {
+                merged_setting.set_leading_comments(comments, line_ending);
+            }
+            if let Some(comment) = self.merge_comments(
+                sides.base.and_then(Setting::inline_comment),
+                sides.ours.inline_comment(),
+                sides.theirs.inline_comment(),
+            ) {
+                merged_setting.set_inline_comment(comment);
+            }
+        }
+
+        if let (ValueType::Nested(our_nested), ValueType::Nested(their_nested)) =
+            (&sides.ours.value, &sides.theirs.value)
+        {
+            let base_nested = sides.base.and_then(|base| match &base.value {
+                ValueType::Nested(base_nested) => Some(base_nested),
+                ValueType::Single(_) => None,
+            });
+            let sides = Sides {
+                base: base_nested,
+                ours: our_nested,
+                theirs: their_nested,
+            };
+            return self.merge_nested_settings(path, sides, merged);
+        }
+
+        let same_outcome = diff_setting(path, Some(sides.ours), Some(sides.theirs)).is_empty();
+        if their_changes.is_empty() || same_outcome {
+            return;
+        }
+
+        match our_changes.is_empty() {
+            true => merged.set_value(&path.setting_name, sides.theirs.value.clone()),
+            false => self.conflict(path.clone(), our_changes, their_changes),
+        }
+    }
+
+    /// Merge a block of nested settings which exists on both sides into its copy in the merged section
+    fn merge_nested_settings<T: Header>(
+        &mut self,
+        setting_path: &SettingPath,
+        sides: Sides<'_, NestedSettings>,
+        merged: &mut Section<T>,
+    ) {
+        let base_nested = sides.base.map(nested_settings_by_name).unwrap_or_default();
+        let our_nested = nested_settings_by_name(sides.ours);
+        let their_nested = nested_settings_by_name(sides.theirs);
+
+        for nested_setting_name in keys(&[&our_nested, &their_nested, &base_nested]) {
+            let path = NestedSettingPath {
+                section_path: setting_path.section_path.clone(),
+                setting_name: setting_path.setting_name.clone(),
+                nested_setting_name: (*nested_setting_name).clone(),
+            };
+            let base = find(&base_nested, nested_setting_name);
+            let ours = find(&our_nested, nested_setting_name);
+            let theirs = find(&their_nested, nested_setting_name);
+
+            let Some(their_change) = diff_nested_setting(&path, base, theirs) else {
+                continue;
+            };
+
+            match (diff_nested_setting(&path, base, ours), theirs) {
+                (None, Some(theirs)) => {
+                    #[allow(clippy::unwrap_used)]
+                    // This cannot fail because the parent setting holds a block of nested settings on our side
+                    merged
+                        .set_nested(
+                            path.setting_name,
+                            path.nested_setting_name,
+                            theirs.value().clone(),
+                        )
+                        .unwrap();
+                }
+                (None, None) => {
+                    merged.remove_nested_setting(&path.setting_name, &path.nested_setting_name);
+                }
+                (Some(_), _) if diff_nested_setting(&path, ours, theirs).is_none() => {}
+                (Some(our_change), _) => self.conflict(path, vec![our_change], vec![their_change]),
+            }
+        }
+    }
+
+    /// Decide whether to take the comments of their side, which is the case if they changed them while we
+    /// didn't. Comments never conflict: if both sides changed them, ours are kept.
+    fn merge_comments<C: PartialEq>(&self, base: C, ours: C, theirs: C) -> Option<C> {
+        let take_theirs = self.comments == Comments::Merge && ours == base && theirs != base;
+
+        take_theirs.then_some(theirs)
+    }
+
+    /// Record a conflict at the given path
+    fn conflict(
+        &mut self,
+        path: impl Into<ConfigPath>,
+        ours: Vec<Difference>,
+        theirs: Vec<Difference>,
+    ) {
+        self.conflicts.push(MergeConflict::new(path, ours, theirs));
+    }
+}
@@ -3,9 +3,12 @@
 //! settings with values which are preceded by at least one space or tab character.
 
 use super::{
-    equal::Equal, indent::Indent, setting_name::SettingName, value::Value, whitespace::Whitespace,
+    equal::Equal, format_options::FormatOptions, indent::Indent, setting_name::SettingName,
+    value::Value, whitespace::Whitespace, LineEnding,
 };
+use crate::error::Expected;
 use crate::lexer::{Parsable, ParserOutput};
+use nom::error::context;
 use std::fmt::Display;
 
 /// Represents a nested setting in its entirety, including indentation, its name and value, and a comment.
@@ -13,6 +16,9 @@ use std::fmt::Display;
 /// to avoid defining an unnecessary recursive type.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NestedSetting {
+    /// Blank lines and comment lines between the previous line of the block and this setting
+    pub(crate) leading_whitespace: Whitespace,
+
     /// Giving the line `region = us-east-2`, 'region' is the [SettingName]
     pub(crate) setting_name: SettingName,
 
@@ -26,8 +32,8 @@ pub struct NestedSetting {
     /// Giving the line `  region = us-east-2`, the leading two spaces are the [Indent].
     pub(crate) leading_spaces: Indent,
 
-    /// Giving the line `  region = us-east-2 # This is a comment`, ' # This is a comment' is the [Whitespace].
-    /// Any whitespace before or after the setting is not included here.
+    /// Giving the line `  region = us-east-2 # This is a comment`, ' # This is a comment' and the line ending
+    /// are the [Whitespace]. Lines before or after the setting are not included here.
     pub(crate) whitespace: Whitespace,
 }
 
@@ -41,14 +47,74 @@ impl NestedSetting {
     pub fn value(&self) -> &Value {
         &self.value
     }
+
+    /// The comment lines directly above the nested setting, without the '#' which starts each of them
+    pub fn leading_comments(&self) -> impl Iterator<Item = &str> {
+        self.leading_whitespace.comments()
+    }
+
+    /// Replace the comment lines above the nested setting. Each comment is written on its own line with the
+    /// same indentation as the nested setting, and a comment which spans several lines becomes several
+    /// comment lines. Each line ends with the given [LineEnding], which should be [crate::Section::line_ending] of the
+    /// section the nested setting is in.
+    pub fn set_leading_comments(
+        &mut self,
+        comments: impl IntoIterator<Item = impl AsRef<str>>,
+        line_ending: LineEnding,
+    ) {
+        self.leading_whitespace.set_comments(
+            comments,
+            &self.leading_spaces.to_string(),
+            line_ending,
+        )
+    }
+
+    /// The number of blank lines which separate this nested setting from the content above it
+    pub fn blank_lines_before(&self) -> usize {
+        self.leading_whitespace.blank_lines()
+    }
+
+    /// Change the number of blank lines which separate this nested setting from the content above it. Each blank
+    /// line ends with the given [LineEnding], which should be [crate::Section::line_ending] of the section the nested
+    /// setting is in.
+    pub fn set_blank_lines_before(&mut self, count: usize, line_ending: LineEnding) {
+        self.leading_whitespace.set_blank_lines(count, line_ending)
+    }
+
+    /// The text of the comment after the value on the same line, without the '#' which starts it
+    pub fn inline_comment(&self) -> Option<&str> {
+        self.whitespace.inline_comment()
+    }
+
+    /// Replace the comment after the value on the same line, or remove it by passing [None]
+    pub fn set_inline_comment(&mut self, comment: Option<&str>) {
+        self.whitespace.set_inline_comment(comment)
+    }
+
+    /// Rewrite the spacing, indentation and line endings of this nested setting as described by the
+    /// [FormatOptions]
+    pub(crate) fn format(&mut self, options: &FormatOptions, line_ending: LineEnding) {
+        self.leading_spaces = options.indent();
+        self.leading_whitespace
+            .format_lines(None, &self.leading_spaces.to_string(), line_ending);
+        self.equal = options.equal();
+        self.whitespace.end_line(line_ending);
+        self.whitespace
+            .format_line_end(options.comment_spacing(), line_ending);
+    }
 }
 
 impl Display for NestedSetting {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}{}{}{}{}",
-            self.leading_spaces, self.setting_name, self.equal, self.value, self.whitespace
+            "{}{}{}{}{}{}",
+            self.leading_whitespace,
+            self.leading_spaces,
+            self.setting_name,
+            self.equal,
+            self.value,
+            self.whitespace
         )
     }
 }
@@ -57,12 +123,21 @@ impl<'a> Parsable<'a> for NestedSetting {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (next, leading_spaces) = Indent::parse(input)?;
+        context(Expected::NestedSetting.label(), Self::parse_inner)(input)
+    }
+}
+
+impl NestedSetting {
+    /// Parses the parts of a [NestedSetting], without attaching a context to the error
+    fn parse_inner(input: &str) -> ParserOutput<'_, Self> {
+        let (next, leading_whitespace) = Whitespace::parse_lines(input)?;
+        let (next, leading_spaces) = Indent::parse(next)?;
         let (next, setting_name) = SettingName::parse(next)?;
         let (next, equal) = Equal::parse(next)?;
         let (next, value) = Value::parse(next)?;
-        let (next, whitespace) = Whitespace::parse(next)?;
+        let (next, whitespace) = Whitespace::parse_line_end(next)?;
         let setting = Self {
+            leading_whitespace,
             setting_name,
             value,
             equal,
@@ -91,4 +166,17 @@ mod test {
 
         assert_eq!(set.to_string(), setting)
     }
+
+    #[test]
+    fn parses_a_value_with_spaces() {
+        let setting = "  user_agent = my tool 1.0 # comment\n";
+
+        let (rest, set) = NestedSetting::parse(setting).expect("Should be valid");
+
+        assert!(rest.is_empty());
+
+        assert_eq!(*"my tool 1.0", *set.value);
+
+        assert_eq!(set.to_string(), setting)
+    }
 }
@@ -1,13 +1,16 @@
 //! Contains items related to parsing and stringifying lists of nested settings.
 
-use super::{whitespace::Whitespace, NestedSetting};
+use super::{
+    format_options::FormatOptions, raw_line::RawLines, style::Style, whitespace::Whitespace,
+    LineEnding, NestedSetting, SettingName, Value,
+};
 use crate::lexer::Parsable;
-use nom::{combinator::map, multi::many0, Parser};
+use nom::{combinator::verify, multi::many0};
 use std::{fmt::Display, ops::Deref};
 
 /// Given the configuration file excert below:
 ///
-/// ```
+/// ```ini
 /// [profile test]
 /// region = us-west-2
 /// s3 =
@@ -25,6 +28,12 @@ pub struct NestedSettings {
 
     /// The list of [NestedSettings]
     pub(crate) nested_settings: Vec<NestedSetting>,
+
+    /// Lines among the nested settings which could not be parsed in lenient mode
+    pub(crate) raw_lines: RawLines,
+
+    /// Indented comment lines directly after the last nested item, which still belong to the block
+    pub(crate) trailing_whitespace: Whitespace,
 }
 
 impl Deref for NestedSettings {
@@ -35,17 +44,114 @@ impl Deref for NestedSettings {
     }
 }
 
+impl NestedSettings {
+    /// Create an empty block, which starts on the line after its parent [crate::Setting]
+    pub(crate) fn new(line_ending: LineEnding) -> Self {
+        Self {
+            leading_whitespace: Whitespace::newline(line_ending),
+            nested_settings: vec![],
+            raw_lines: RawLines::default(),
+            trailing_whitespace: Whitespace::default(),
+        }
+    }
+
+    /// Change the [Value] of an existing [NestedSetting], or add one to the end of the block. A new setting is
+    /// indented like the first setting already in the block, or as the [Style] says if the block is empty.
+    pub(crate) fn set(&mut self, setting_name: SettingName, value: Value, style: &Style) {
+        let line_ending = style.line_ending;
+
+        if let Some(nested) = self
+            .nested_settings
+            .iter_mut()
+            .find(|nested| nested.name() == &setting_name)
+        {
+            nested.value = value;
+            return;
+        }
+
+        let leading_spaces = self
+            .nested_settings
+            .first()
+            .map(|nested| nested.leading_spaces.clone())
+            .unwrap_or_else(|| style.nested_indent.clone());
+
+        match self.nested_settings.last_mut() {
+            Some(nested) => nested.whitespace.end_line(line_ending),
+            None => self.leading_whitespace.end_line(line_ending),
+        }
+
+        let len = self.nested_settings.len();
+        self.raw_lines.item_inserted(len, len);
+        self.nested_settings.push(NestedSetting {
+            leading_whitespace: Whitespace::default(),
+            leading_spaces,
+            setting_name,
+            equal: style.equal.clone(),
+            value,
+            whitespace: Whitespace::newline(line_ending),
+        });
+    }
+
+    /// Remove the [NestedSetting] with the given name, returning it if it existed. Comment lines above it are
+    /// removed with it, while blank lines above those comments stay in place.
+    pub(crate) fn remove(&mut self, setting_name: &SettingName) -> Option<NestedSetting> {
+        let index = self
+            .nested_settings
+            .iter()
+            .position(|nested| nested.name() == setting_name)?;
+        let mut nested = self.nested_settings.remove(index);
+        let separator = nested.leading_whitespace.take_separator();
+        self.raw_lines.item_removed(index);
+
+        if let Some(next) = self.nested_settings.get_mut(index) {
+            next.leading_whitespace.merge_separator(separator);
+        }
+
+        Some(nested)
+    }
+
+    /// Rewrite the spacing, indentation and line endings of the block as described by the [FormatOptions], and
+    /// sort it if they ask for it. Lines kept by lenient parsing stay at their position.
+    pub(crate) fn format(&mut self, options: &FormatOptions, line_ending: LineEnding) {
+        if options.sort_settings {
+            self.nested_settings
+                .sort_by(|first, second| first.name().cmp(second.name()));
+        }
+
+        self.leading_whitespace.end_line(line_ending);
+        self.leading_whitespace
+            .format_line_end(options.comment_spacing(), line_ending);
+        self.nested_settings
+            .iter_mut()
+            .for_each(|nested| nested.format(options, line_ending));
+        self.raw_lines
+            .format(&options.indent().to_string(), line_ending);
+        self.trailing_whitespace
+            .format_lines(Some(0), &options.indent().to_string(), line_ending);
+    }
+
+    /// The whitespace holding the line ending of the last line of the block
+    pub(crate) fn line_end_mut(&mut self) -> &mut Whitespace {
+        if !self.trailing_whitespace.is_empty() {
+            return &mut self.trailing_whitespace;
+        }
+
+        let len = self.nested_settings.len();
+        match self.raw_lines.line_end_after_mut(len) {
+            Some(line_end) => line_end,
+            None => match self.nested_settings.last_mut() {
+                Some(nested) => &mut nested.whitespace,
+                None => &mut self.leading_whitespace,
+            },
+        }
+    }
+}
+
 impl Display for NestedSettings {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}{}",
-            self.leading_whitespace,
-            self.nested_settings
-                .iter()
-                .map(NestedSetting::to_string)
-                .collect::<String>()
-        )
+        write!(f, "{}", self.leading_whitespace)?;
+        self.raw_lines.write_with(f, &self.nested_settings)?;
+        write!(f, "{}", self.trailing_whitespace)
     }
 }
 
@@ -53,18 +159,19 @@ impl<'a> Parsable<'a> for NestedSettings {
     type Output = Self;
 
     fn parse(input: &'a str) -> crate::lexer::ParserOutput<'a, Self::Output> {
-        map(
-            Whitespace::parse.and(many0(NestedSetting::parse)),
-            Self::from,
-        )(input)
-    }
-}
+        let (next, leading_whitespace) = Whitespace::parse_line_end(input)?;
+        let (next, nested_settings) = many0(verify(NestedSetting::parse, |nested| {
+            !nested.leading_spaces.is_empty()
+        }))(next)?;
+        let (next, trailing_whitespace) = Whitespace::parse_indented_comments(next)?;
 
-impl From<(Whitespace, Vec<NestedSetting>)> for NestedSettings {
-    fn from((leading_whitespace, nested_settings): (Whitespace, Vec<NestedSetting>)) -> Self {
-        Self {
+        let nested_settings = Self {
             leading_whitespace,
             nested_settings,
-        }
+            raw_lines: RawLines::default(),
+            trailing_whitespace,
+        };
+
+        Ok((next, nested_settings))
     }
 }
@@ -0,0 +1,65 @@
+//! Contains the [Output] type, which is the typed value of the `output` setting.
+
+use crate::Error;
+use std::{fmt::Display, str::FromStr};
+
+/// The format in which the AWS CLI prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Output {
+    /// `json`
+    Json,
+
+    /// `yaml`
+    Yaml,
+
+    /// `yaml-stream`, which prints YAML documents as they arrive
+    YamlStream,
+
+    /// `text`, which prints tab-separated values
+    Text,
+
+    /// `table`
+    Table,
+}
+
+impl Output {
+    /// Every variant, used to find one by its name
+    const ALL: [Self; 5] = [
+        Self::Json,
+        Self::Yaml,
+        Self::YamlStream,
+        Self::Text,
+        Self::Table,
+    ];
+
+    /// The name of the format, as it is written in the config file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::YamlStream => "yaml-stream",
+            Self::Text => "text",
+            Self::Table => "table",
+        }
+    }
+}
+
+impl FromStr for Output {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|output| output.as_str() == s)
+            .ok_or_else(|| Error::InvalidValue {
+                value: s.to_string(),
+                expected: "one of json, yaml, yaml-stream, text or table",
+            })
+    }
+}
+
+impl Display for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
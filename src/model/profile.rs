@@ -0,0 +1,331 @@
+//! Contains the [Profile] type, a strongly typed view of a profile [Section] of an [crate::AwsConfigFile].
+
+use super::{
+    header::ConfigHeader, AccountId, Arn, CredentialSource, EndpointUrl, Output, Region, Section,
+    SectionName, SectionType, Value,
+};
+use crate::Error;
+use std::{
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// A strongly typed view of a profile [Section], with a getter and a setter for each of the standard profile
+/// settings. It is created by [crate::AwsConfigFile::profile] and [crate::AwsConfigFile::profile_mut], or by
+/// [Profile::new] from any profile section. A view of a shared reference can only be read, while a view of a
+/// mutable reference can also be changed.
+///
+/// Getters return [None] if the setting is missing, and an [Error::InvalidSetting] if its value doesn't have the
+/// required form, such as an `output` which isn't one of the known formats. Settings which accept any text,
+/// such as `external_id`, are returned as a plain [Value]. Setters go through [Section::set], so a setting which
+/// already exists is changed in place, keeping its formatting and comments, and a new one is added to the end
+/// of the profile.
+///
+/// ```
+/// # use aws_config_mod::{AwsConfigFile, Output, Region};
+/// let mut config: AwsConfigFile = "[profile dev]\nregion = us-east-1 # home\noutput = json\n"
+///     .parse()
+///     .unwrap();
+/// let profile_name = "dev".parse().unwrap();
+///
+/// let profile = config.profile(&profile_name).unwrap();
+/// assert_eq!(profile.output().unwrap(), Some(Output::Json));
+///
+/// let mut profile = config.profile_mut(&profile_name).unwrap();
+/// profile.set_region("eu-west-1".parse::<Region>().unwrap());
+///
+/// assert_eq!(config.to_string(), "[profile dev]\nregion = eu-west-1 # home\noutput = json\n");
+/// ```
+#[derive(Debug)]
+pub struct Profile<S> {
+    /// The profile section this is a view of
+    section: S,
+}
+
+impl<S> Profile<S>
+where
+    S: Deref<Target = Section<ConfigHeader>>,
+{
+    /// The name of the setting for the region
+    const REGION: &'static str = "region";
+
+    /// The name of the setting for the output format
+    const OUTPUT: &'static str = "output";
+
+    /// The name of the setting for the role to assume
+    const ROLE_ARN: &'static str = "role_arn";
+
+    /// The name of the setting for the profile whose credentials assume the role
+    const SOURCE_PROFILE: &'static str = "source_profile";
+
+    /// The name of the setting for the source of the credentials which assume the role
+    const CREDENTIAL_SOURCE: &'static str = "credential_source";
+
+    /// The name of the setting for the external ID used to assume the role
+    const EXTERNAL_ID: &'static str = "external_id";
+
+    /// The name of the setting for the MFA device used to assume the role
+    const MFA_SERIAL: &'static str = "mfa_serial";
+
+    /// The name of the setting for the name of the role session
+    const ROLE_SESSION_NAME: &'static str = "role_session_name";
+
+    /// The name of the setting for the duration of the role session
+    const DURATION_SECONDS: &'static str = "duration_seconds";
+
+    /// The name of the setting for the file holding a web identity token
+    const WEB_IDENTITY_TOKEN_FILE: &'static str = "web_identity_token_file";
+
+    /// The name of the setting for the command which provides credentials
+    const CREDENTIAL_PROCESS: &'static str = "credential_process";
+
+    /// The name of the setting for the SSO session
+    const SSO_SESSION: &'static str = "sso_session";
+
+    /// The name of the setting for the account accessed through SSO
+    const SSO_ACCOUNT_ID: &'static str = "sso_account_id";
+
+    /// The name of the setting for the role used through SSO
+    const SSO_ROLE_NAME: &'static str = "sso_role_name";
+
+    /// The name of the setting for the services section
+    const SERVICES: &'static str = "services";
+
+    /// The name of the setting for the endpoint used by all services
+    const ENDPOINT_URL: &'static str = "endpoint_url";
+
+    /// The range of durations which AWS accepts for a role session
+    const DURATION_RANGE: std::ops::RangeInclusive<u64> = 900..=43200;
+
+    /// Create a view of the given section, or return [None] if it isn't a profile
+    pub fn new(section: S) -> Option<Self> {
+        (*section.get_type() == SectionType::Profile).then_some(Self { section })
+    }
+
+    /// The profile section this is a view of
+    pub fn section(&self) -> &Section<ConfigHeader> {
+        &self.section
+    }
+
+    /// The name of the profile
+    pub fn name(&self) -> Option<&SectionName> {
+        self.section.get_name()
+    }
+
+    /// The region used by default, from the `region` setting
+    pub fn region(&self) -> Result<Option<Region>, Error> {
+        self.section.typed_value(Self::REGION)
+    }
+
+    /// The format in which results are printed, from the `output` setting
+    pub fn output(&self) -> Result<Option<Output>, Error> {
+        self.section.typed_value(Self::OUTPUT)
+    }
+
+    /// The role which is assumed, from the `role_arn` setting
+    pub fn role_arn(&self) -> Result<Option<Arn>, Error> {
+        self.section.typed_value(Self::ROLE_ARN)
+    }
+
+    /// The profile whose credentials are used to assume the role, from the `source_profile` setting
+    pub fn source_profile(&self) -> Result<Option<SectionName>, Error> {
+        self.section.typed_value(Self::SOURCE_PROFILE)
+    }
+
+    /// Where the credentials used to assume the role come from, from the `credential_source` setting
+    pub fn credential_source(&self) -> Result<Option<CredentialSource>, Error> {
+        self.section.typed_value(Self::CREDENTIAL_SOURCE)
+    }
+
+    /// The external ID passed when assuming the role, from the `external_id` setting
+    pub fn external_id(&self) -> Option<&Value> {
+        self.section.single_value(Self::EXTERNAL_ID)
+    }
+
+    /// The serial number or ARN of the MFA device used when assuming the role, from the `mfa_serial` setting
+    pub fn mfa_serial(&self) -> Option<&Value> {
+        self.section.single_value(Self::MFA_SERIAL)
+    }
+
+    /// The name given to the role session, from the `role_session_name` setting
+    pub fn role_session_name(&self) -> Option<&Value> {
+        self.section.single_value(Self::ROLE_SESSION_NAME)
+    }
+
+    /// How long the role session lasts, from the `duration_seconds` setting, which AWS accepts between 15
+    /// minutes and 12 hours
+    pub fn duration_seconds(&self) -> Result<Option<Duration>, Error> {
+        let Some(value) = self.section.single_value(Self::DURATION_SECONDS) else {
+            return Ok(None);
+        };
+
+        match value.parse::<u64>() {
+            Ok(seconds) if Self::DURATION_RANGE.contains(&seconds) => {
+                Ok(Some(Duration::from_secs(seconds)))
+            }
+            _ => Err(Error::invalid_setting(
+                Self::DURATION_SECONDS,
+                Self::invalid_duration(value),
+            )),
+        }
+    }
+
+    /// The file holding the token used to assume the role with a web identity, from the
+    /// `web_identity_token_file` setting
+    pub fn web_identity_token_file(&self) -> Option<PathBuf> {
+        self.section
+            .single_value(Self::WEB_IDENTITY_TOKEN_FILE)
+            .map(|value| PathBuf::from(&**value))
+    }
+
+    /// The command which provides the credentials, from the `credential_process` setting
+    pub fn credential_process(&self) -> Option<&Value> {
+        self.section.single_value(Self::CREDENTIAL_PROCESS)
+    }
+
+    /// The `[sso-session]` section used to sign in, from the `sso_session` setting
+    pub fn sso_session(&self) -> Result<Option<SectionName>, Error> {
+        self.section.typed_value(Self::SSO_SESSION)
+    }
+
+    /// The account which is accessed through SSO, from the `sso_account_id` setting
+    pub fn sso_account_id(&self) -> Result<Option<AccountId>, Error> {
+        self.section.typed_value(Self::SSO_ACCOUNT_ID)
+    }
+
+    /// The role which is used through SSO, from the `sso_role_name` setting
+    pub fn sso_role_name(&self) -> Option<&Value> {
+        self.section.single_value(Self::SSO_ROLE_NAME)
+    }
+
+    /// The `[services]` section which configures the endpoints of individual services, from the `services`
+    /// setting
+    pub fn services(&self) -> Result<Option<SectionName>, Error> {
+        self.section.typed_value(Self::SERVICES)
+    }
+
+    /// The endpoint used by all services, from the `endpoint_url` setting
+    pub fn endpoint_url(&self) -> Result<Option<EndpointUrl>, Error> {
+        self.section.typed_value(Self::ENDPOINT_URL)
+    }
+
+    /// Describe a number of seconds which isn't a valid duration for a role session
+    fn invalid_duration(seconds: &str) -> Error {
+        Error::InvalidValue {
+            value: seconds.to_string(),
+            expected: "a number of seconds between 900 and 43200",
+        }
+    }
+}
+
+impl<S> Profile<S>
+where
+    S: DerefMut<Target = Section<ConfigHeader>>,
+{
+    /// The profile section this is a view of, which can be changed directly, such as to remove a setting
+    pub fn section_mut(&mut self) -> &mut Section<ConfigHeader> {
+        &mut self.section
+    }
+
+    /// Set the `region` setting
+    pub fn set_region(&mut self, region: Region) {
+        self.section.set_str(Self::REGION, &region)
+    }
+
+    /// Set the `output` setting
+    pub fn set_output(&mut self, output: Output) {
+        self.section.set_str(Self::OUTPUT, output.as_str())
+    }
+
+    /// Set the `role_arn` setting
+    pub fn set_role_arn(&mut self, role_arn: Arn) {
+        self.section.set_str(Self::ROLE_ARN, &role_arn)
+    }
+
+    /// Set the `source_profile` setting
+    pub fn set_source_profile(&mut self, source_profile: SectionName) {
+        self.section.set_str(Self::SOURCE_PROFILE, &source_profile)
+    }
+
+    /// Set the `credential_source` setting
+    pub fn set_credential_source(&mut self, credential_source: CredentialSource) {
+        self.section
+            .set_str(Self::CREDENTIAL_SOURCE, credential_source.as_str())
+    }
+
+    /// Set the `external_id` setting
+    pub fn set_external_id(&mut self, external_id: Value) {
+        self.section.set_str(Self::EXTERNAL_ID, &external_id)
+    }
+
+    /// Set the `mfa_serial` setting
+    pub fn set_mfa_serial(&mut self, mfa_serial: Value) {
+        self.section.set_str(Self::MFA_SERIAL, &mfa_serial)
+    }
+
+    /// Set the `role_session_name` setting
+    pub fn set_role_session_name(&mut self, role_session_name: Value) {
+        self.section
+            .set_str(Self::ROLE_SESSION_NAME, &role_session_name)
+    }
+
+    /// Set the `duration_seconds` setting, in whole seconds. Fails with [Error::InvalidValue] without changing
+    /// anything if the duration is shorter than 15 minutes or longer than 12 hours.
+    pub fn set_duration_seconds(&mut self, duration: Duration) -> Result<(), Error> {
+        let seconds = duration.as_secs().to_string();
+        if !Self::DURATION_RANGE.contains(&duration.as_secs()) {
+            return Err(Self::invalid_duration(&seconds));
+        }
+
+        self.section.set_str(Self::DURATION_SECONDS, &seconds);
+        Ok(())
+    }
+
+    /// Set the `web_identity_token_file` setting. Fails with [Error::InvalidValue] without changing anything if
+    /// the path isn't valid UTF-8 or wouldn't be read back as the same [Value], such as a path which spans
+    /// several lines or holds ` #`, which starts a comment.
+    pub fn set_web_identity_token_file(&mut self, path: &Path) -> Result<(), Error> {
+        let value = path
+            .to_str()
+            .and_then(|path| path.parse::<Value>().ok())
+            .ok_or_else(|| Error::InvalidValue {
+                value: path.to_string_lossy().into_owned(),
+                expected: "a path of valid UTF-8 which can be written as a single value",
+            })?;
+
+        self.section.set_str(Self::WEB_IDENTITY_TOKEN_FILE, &value);
+        Ok(())
+    }
+
+    /// Set the `credential_process` setting
+    pub fn set_credential_process(&mut self, credential_process: Value) {
+        self.section
+            .set_str(Self::CREDENTIAL_PROCESS, &credential_process)
+    }
+
+    /// Set the `sso_session` setting
+    pub fn set_sso_session(&mut self, sso_session: SectionName) {
+        self.section.set_str(Self::SSO_SESSION, &sso_session)
+    }
+
+    /// Set the `sso_account_id` setting
+    pub fn set_sso_account_id(&mut self, sso_account_id: AccountId) {
+        self.section.set_str(Self::SSO_ACCOUNT_ID, &sso_account_id)
+    }
+
+    /// Set the `sso_role_name` setting
+    pub fn set_sso_role_name(&mut self, sso_role_name: Value) {
+        self.section.set_str(Self::SSO_ROLE_NAME, &sso_role_name)
+    }
+
+    /// Set the `services` setting
+    pub fn set_services(&mut self, services: SectionName) {
+        self.section.set_str(Self::SERVICES, &services)
+    }
+
+    /// Set the `endpoint_url` setting
+    pub fn set_endpoint_url(&mut self, endpoint_url: EndpointUrl) {
+        self.section.set_str(Self::ENDPOINT_URL, &endpoint_url)
+    }
+}
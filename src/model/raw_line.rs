@@ -0,0 +1,149 @@
+//! Contains the [RawLine] type, which holds a line that could not be parsed in lenient mode, and the [RawLines]
+//! of a list of items.
+
+use super::{whitespace::Whitespace, LineEnding};
+use std::fmt::Display;
+
+/// A line which could not be parsed, kept verbatim by [crate::AwsConfigFile::parse_lenient] so that the file is
+/// written back out unchanged. It is a node of its own: it is never removed along with a neighbouring item, and
+/// it keeps its place when the items around it are reordered.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct RawLine {
+    /// The number of items of the surrounding list which come before this line
+    pub(crate) position: usize,
+
+    /// Blank lines and comment lines between the previous line and this one
+    pub(crate) leading_whitespace: Whitespace,
+
+    /// The text of the line, without its line ending
+    pub(crate) line: String,
+
+    /// The line ending of the line, which is only missing on the last line of the file
+    pub(crate) line_end: Whitespace,
+}
+
+impl RawLine {
+    /// Split a whole line, including its line ending, into a [RawLine] at the given position, which owns the
+    /// `leading_whitespace` before it
+    pub(crate) fn new(position: usize, leading_whitespace: Whitespace, line: &str) -> Self {
+        let text = line.trim_end_matches(['\r', '\n']);
+
+        Self {
+            position,
+            leading_whitespace,
+            line: text.to_string(),
+            line_end: Whitespace(line[text.len()..].to_string()),
+        }
+    }
+}
+
+impl Display for RawLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.leading_whitespace, self.line, self.line_end
+        )
+    }
+}
+
+/// The [RawLine]s among a list of items, such as the settings of a section, in the order they appear. Each of
+/// them is placed by the number of items before it rather than by its neighbours, so moving or removing an item
+/// never moves a raw line past another item. Raw lines after the last item stay after it when items are added.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub(crate) struct RawLines(Vec<RawLine>);
+
+impl RawLines {
+    /// Whether there are no raw lines
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Add the given raw lines after the ones already held, at the given position
+    pub(crate) fn append(&mut self, raw_lines: Vec<RawLine>, position: usize) {
+        self.0.extend(raw_lines.into_iter().map(|raw_line| RawLine {
+            position,
+            ..raw_line
+        }))
+    }
+
+    /// The text of each raw line, without its line ending
+    pub(crate) fn lines(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|raw_line| raw_line.line.as_str())
+    }
+
+    /// The raw lines which come directly before the item at the given position, or after the last item if the
+    /// position is the number of items
+    pub(crate) fn at(&self, position: usize) -> impl Iterator<Item = &RawLine> {
+        self.0
+            .iter()
+            .filter(move |raw_line| raw_line.position == position)
+    }
+
+    /// Keep every raw line in place when an item is inserted at `index` into a list which held `len` items
+    pub(crate) fn item_inserted(&mut self, index: usize, len: usize) {
+        self.0
+            .iter_mut()
+            .filter(|raw_line| raw_line.position > index || raw_line.position == len)
+            .for_each(|raw_line| raw_line.position += 1)
+    }
+
+    /// Keep every raw line in place when the item at `index` is removed
+    pub(crate) fn item_removed(&mut self, index: usize) {
+        self.0
+            .iter_mut()
+            .filter(|raw_line| raw_line.position > index)
+            .for_each(|raw_line| raw_line.position -= 1)
+    }
+
+    /// The line ending of the last raw line, if it comes after the last of `len` items
+    pub(crate) fn line_end_after_mut(&mut self, len: usize) -> Option<&mut Whitespace> {
+        self.0
+            .last_mut()
+            .filter(|raw_line| raw_line.position == len)
+            .map(|raw_line| &mut raw_line.line_end)
+    }
+
+    /// Format the lines before each raw line, and end each of them with the given line ending. The text of
+    /// the raw lines is left alone.
+    pub(crate) fn format(&mut self, indent: &str, line_ending: LineEnding) {
+        for raw_line in &mut self.0 {
+            raw_line
+                .leading_whitespace
+                .format_lines(None, indent, line_ending);
+            raw_line.line_end = Whitespace::newline(line_ending);
+        }
+    }
+
+    /// Write the raw lines at the given position
+    pub(crate) fn write_at(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        position: usize,
+    ) -> std::fmt::Result {
+        self.at(position)
+            .try_for_each(|raw_line| write!(f, "{raw_line}"))
+    }
+
+    /// Write the raw lines interleaved with the given items
+    pub(crate) fn write_with<I: Display>(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        items: &[I],
+    ) -> std::fmt::Result {
+        for (position, item) in items.iter().enumerate() {
+            self.write_at(f, position)?;
+            write!(f, "{item}")?;
+        }
+
+        self.write_at(f, items.len())
+    }
+}
+
+impl Display for RawLines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|raw_line| write!(f, "{raw_line}"))
+    }
+}
@@ -0,0 +1,75 @@
+//! Contains the [Region] type, which is the typed value of settings such as `region` and `sso_region`.
+
+use crate::Error;
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+/// The name of an AWS region, such as `us-east-1`. It is made of lowercase letters and digits, in at least two
+/// parts separated by '-'. Whether the region actually exists is not checked, so that new regions can be used
+/// without updating this crate.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Region(String);
+
+impl FromStr for Region {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split('-').collect::<Vec<_>>();
+        let is_valid = parts.len() >= 2
+            && parts.iter().all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            });
+
+        match is_valid {
+            true => Ok(Self(s.to_string())),
+            false => Err(Error::InvalidValue {
+                value: s.to_string(),
+                expected: "a region such as 'us-east-1'",
+            }),
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for Region {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Region;
+
+    #[test]
+    fn regions_of_every_partition_are_valid() {
+        for region in ["us-east-1", "eu-central-2", "us-gov-west-1", "cn-north-1"] {
+            let parsed = region.parse::<Region>().expect("Should be valid");
+
+            assert_eq!(parsed.to_string(), region);
+        }
+    }
+
+    #[test]
+    fn malformed_regions_are_rejected() {
+        for region in [
+            "",
+            "useast1",
+            "US-EAST-1",
+            "us-east-",
+            "us--east-1",
+            "us east 1",
+        ] {
+            assert!(region.parse::<Region>().is_err(), "{region}");
+        }
+    }
+}
@@ -1,22 +1,35 @@
 //! Contains items related to parsing and stringifying entire sections, including the header and all the settings.
 
+#[cfg(feature = "serde")]
+use super::serde_section::{update_section, SectionDeserializer};
 use super::{
-    header::{ConfigHeader, CredentialHeader},
+    entry::{OccupiedSettingEntry, SettingEntry, VacantSettingEntry},
+    format_options::FormatOptions,
+    header::{ConfigHeader, CredentialHeader, Header},
     nested_setting::NestedSetting,
+    nested_settings::NestedSettings,
+    raw_line::{RawLine, RawLines},
+    style::Style,
     whitespace::Whitespace,
-    SectionName, SectionType, Setting, SettingName, Value, ValueType,
+    LineEnding, SectionName, SectionType, Setting, SettingName, Value, ValueType,
 };
+use crate::error::{Error, Expected};
 use crate::lexer::{Parsable, ParserOutput};
-use nom::multi::many0;
+use nom::{
+    error::{VerboseError, VerboseErrorKind},
+    multi::many0,
+};
 use std::{
+    cmp::Ordering,
     fmt::{Debug, Display},
     hash::Hash,
+    str::FromStr,
 };
 
 /// Represents an entire section, including the section type, the profile name, and all of the settings
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
 pub struct Section<T> {
-    /// Any whitespace or comments that appear before the section
+    /// The comment lines directly above the header, along with any blank lines above those comments
     pub(crate) leading_whitespace: Whitespace,
 
     /// The section header, which contains the optional section type and the section name
@@ -25,26 +38,111 @@ pub struct Section<T> {
     /// The list of settings for the section
     pub(crate) settings: Vec<Setting>,
 
-    /// Any whitespace or comments that appear after the section
+    /// Lines among the settings which could not be parsed in lenient mode
+    pub(crate) raw_lines: RawLines,
+
+    /// Any blank lines and comment lines after the last setting which are not attached to whatever follows
     pub(crate) trailing_whitespace: Whitespace,
+
+    /// The formatting used for any lines added to this section
+    pub(crate) style: Style,
 }
 
 impl<T> Section<T>
 where
-    T: Default,
+    T: Header,
 {
     /// Create a new section, without any settings.
     pub fn new(header: T) -> Self {
+        Self::with_style(header, &Style::default())
+    }
+
+    /// Create a new section, without any settings, whose header line and any lines added to it are formatted
+    /// in the given [Style]
+    pub(crate) fn with_style(mut header: T, style: &Style) -> Self {
+        header.line_end_mut().end_line(style.line_ending);
+
         Self {
             header,
+            style: style.clone(),
             ..Default::default()
         }
     }
 
+    /// Lines which could not be parsed when this section was read in lenient mode. They are kept as-is
+    /// and written back out unchanged.
+    pub fn raw_lines(&self) -> impl Iterator<Item = &str> {
+        (0..=self.settings.len()).flat_map(|position| {
+            self.raw_lines
+                .at(position)
+                .map(|raw_line| raw_line.line.as_str())
+                .chain(
+                    self.settings
+                        .get(position)
+                        .into_iter()
+                        .flat_map(Setting::raw_lines),
+                )
+        })
+    }
+
+    /// The line ending used for any lines added to this section
+    pub fn line_ending(&self) -> LineEnding {
+        self.style.line_ending
+    }
+
     /// Retrieve the [SectionType] of this [Section]
     pub fn get_type(&self) -> &SectionType {
-        todo!()
-        // &self.header.section_type
+        self.header.section_type()
+    }
+
+    /// The comment lines directly above the header, without the '#' which starts each of them
+    pub fn leading_comments(&self) -> impl Iterator<Item = &str> {
+        self.leading_whitespace.comments()
+    }
+
+    /// Replace the comment lines directly above the header. Each comment is written on its own line, and a
+    /// comment which spans several lines becomes several comment lines.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, SectionPath};
+    /// let mut config: AwsConfigFile = "[profile A]\nregion = us-east-1\n".parse().unwrap();
+    /// let section_path = SectionPath::try_from("profile.A").unwrap();
+    ///
+    /// let section = config.section_entry(section_path).or_insert();
+    /// section.set_leading_comments(["owner: platform-team"]);
+    /// section.set_inline_comment(Some("production"));
+    ///
+    /// assert_eq!(config.to_string(), "# owner: platform-team\n[profile A] # production\nregion = us-east-1\n");
+    /// ```
+    pub fn set_leading_comments(&mut self, comments: impl IntoIterator<Item = impl AsRef<str>>) {
+        self.leading_whitespace
+            .set_comments(comments, "", self.style.line_ending)
+    }
+
+    /// The number of blank lines which separate this section from the content above it
+    pub fn blank_lines_before(&self) -> usize {
+        self.leading_whitespace.blank_lines()
+    }
+
+    /// Change the number of blank lines which separate this section from the content above it
+    pub fn set_blank_lines_before(&mut self, count: usize) {
+        self.leading_whitespace
+            .set_blank_lines(count, self.style.line_ending)
+    }
+
+    /// The text of the comment after the header on the same line, without the '#' which starts it
+    pub fn inline_comment(&self) -> Option<&str> {
+        self.header.inline_comment()
+    }
+
+    /// Replace the comment after the header on the same line, or remove it by passing [None]
+    pub fn set_inline_comment(&mut self, comment: Option<&str>) {
+        self.header.line_end_mut().set_inline_comment(comment)
+    }
+
+    /// Get an immutable reference to the header of this section
+    pub fn header(&self) -> &T {
+        &self.header
     }
 
     /// Get an immutable reference to the [Setting]s of this section
@@ -67,13 +165,6 @@ where
             .map(|setting| setting.value())
     }
 
-    /// Update the value attached to a given setting name.
-    pub fn set_value(&mut self, setting_name: &SettingName, value: ValueType) {
-        if let Some(setting) = self.get_setting_mut(setting_name) {
-            setting.value = value
-        }
-    }
-
     /// Retrieve a mutable reference to a [Setting] by its [SettingName], if it exists
     pub fn get_setting_mut(&mut self, setting_name: &SettingName) -> Option<&mut Setting> {
         self.settings
@@ -97,14 +188,476 @@ where
         }
     }
 
-    /// Change the [Value] of an existing [Setting]
+    /// Lookup a mutable reference to a [NestedSetting], which is a setting nested under another setting,
+    /// if it exists.
+    pub fn get_nested_setting_mut(
+        &mut self,
+        setting_name: &SettingName,
+        nested_setting_name: &SettingName,
+    ) -> Option<&mut NestedSetting> {
+        match &mut self.get_setting_mut(setting_name)?.value {
+            ValueType::Single(_) => None,
+            ValueType::Nested(nested) => nested
+                .nested_settings
+                .iter_mut()
+                .find(|setting| setting.name() == nested_setting_name),
+        }
+    }
+
+    /// Update the value attached to a given setting name.
+    pub fn set_value(&mut self, setting_name: &SettingName, value: ValueType) {
+        if let Some(setting) = self.get_setting_mut(setting_name) {
+            setting.value = value
+        }
+    }
+
+    /// Change the [Value] of a [Setting], adding the [Setting] to the end of the section if it doesn't exist
     pub fn set(&mut self, setting_name: SettingName, value: Value) {
-        if let Some(setting) = self.get_setting_mut(&setting_name) {
-            setting.value = ValueType::Single(value);
-        } else {
-            let value = ValueType::Single(value);
-            let setting = Setting::new(setting_name, value);
-            self.settings.push(setting)
+        match self.entry(setting_name) {
+            SettingEntry::Occupied(mut entry) => entry.get_mut().set(value),
+            SettingEntry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+
+    /// The single [Value] of the setting with the given name, if it has one. Used by the typed views such as
+    /// [crate::Profile], which know their setting names as strings.
+    pub(crate) fn single_value(&self, setting_name: &str) -> Option<&Value> {
+        match self.get_value(&SettingName(setting_name.to_string()))? {
+            ValueType::Single(value) => Some(value),
+            ValueType::Nested(_) => None,
+        }
+    }
+
+    /// The single [Value] of the setting with the given name, converted to a typed value. Fails with
+    /// [Error::InvalidSetting] if the value doesn't have the required form.
+    pub(crate) fn typed_value<V: FromStr<Err = Error>>(
+        &self,
+        setting_name: &str,
+    ) -> Result<Option<V>, Error> {
+        self.single_value(setting_name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|error| Error::invalid_setting(setting_name, error))
+            })
+            .transpose()
+    }
+
+    /// Change the value of the setting with the given name through [Section::set]
+    pub(crate) fn set_str(&mut self, setting_name: &str, value: &str) {
+        self.set(SettingName(setting_name.to_string()), Value::from(value))
+    }
+
+    /// Look up the [Setting] with the given [SettingName] once, returning a [SettingEntry] which can be used to
+    /// change the [Setting] if it exists or to create it otherwise.
+    pub fn entry(&mut self, setting_name: SettingName) -> SettingEntry<'_, T> {
+        let index = self
+            .settings
+            .iter()
+            .position(|setting| setting.name() == &setting_name);
+
+        match index {
+            Some(index) => SettingEntry::Occupied(OccupiedSettingEntry {
+                section: self,
+                index,
+            }),
+            None => SettingEntry::Vacant(VacantSettingEntry {
+                section: self,
+                setting_name,
+            }),
+        }
+    }
+
+    /// Add a [Setting] with the given [Value] directly in front of the setting named `anchor`, taking over the
+    /// blank lines above it, and return a mutable reference to it. If a setting with the given [SettingName]
+    /// already exists, its value is changed where it is instead. Returns [None] without changing anything if
+    /// there is no setting named `anchor`.
+    pub fn insert_setting_before(
+        &mut self,
+        anchor: &SettingName,
+        setting_name: SettingName,
+        value: Value,
+    ) -> Option<&mut Setting> {
+        let index = self.setting_index(anchor)?;
+
+        Some(self.insert_setting_at(index, setting_name, value))
+    }
+
+    /// Add a [Setting] with the given [Value] directly after the setting named `anchor`, including its nested
+    /// settings, and return a mutable reference to it. If a setting with the given [SettingName] already
+    /// exists, its value is changed where it is instead. Returns [None] without changing anything if there is
+    /// no setting named `anchor`.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, SectionPath, Value};
+    /// let mut config: AwsConfigFile = "[profile A]\nregion = us-east-1\noutput = json\n".parse().unwrap();
+    /// let section_path = SectionPath::try_from("profile.A").unwrap();
+    /// let section = config.section_entry(section_path).or_insert();
+    ///
+    /// let region = "region".parse().unwrap();
+    /// section.insert_setting_after(&region, "sts_regional_endpoints".parse().unwrap(), Value::from("regional"));
+    ///
+    /// assert_eq!(
+    ///     config.to_string(),
+    ///     "[profile A]\nregion = us-east-1\nsts_regional_endpoints = regional\noutput = json\n"
+    /// );
+    /// ```
+    pub fn insert_setting_after(
+        &mut self,
+        anchor: &SettingName,
+        setting_name: SettingName,
+        value: Value,
+    ) -> Option<&mut Setting> {
+        let index = self.setting_index(anchor)?;
+
+        Some(self.insert_setting_at(index + 1, setting_name, value))
+    }
+
+    /// Find the position of the [Setting] with the given [SettingName]
+    pub(crate) fn setting_index(&self, setting_name: &SettingName) -> Option<usize> {
+        self.settings
+            .iter()
+            .position(|setting| setting.name() == setting_name)
+    }
+
+    /// Add a [Setting] at the given position, or change its value where it is if it already exists. The new
+    /// setting takes over the blank lines above the setting it is inserted in front of, and is indented like
+    /// its neighbours.
+    fn insert_setting_at(
+        &mut self,
+        index: usize,
+        setting_name: SettingName,
+        value: Value,
+    ) -> &mut Setting {
+        if let Some(existing) = self.setting_index(&setting_name) {
+            let setting = &mut self.settings[existing];
+            setting.set(value);
+            return setting;
+        }
+
+        let mut setting = Setting::with_style(setting_name, ValueType::Single(value), &self.style);
+        let indentation = self
+            .settings
+            .get(index)
+            .or_else(|| self.settings.get(index.checked_sub(1)?))
+            .map(|neighbour| neighbour.leading_whitespace.indentation().to_string())
+            .unwrap_or_default();
+        setting.leading_whitespace.0.push_str(&indentation);
+
+        self.place_setting(index, setting)
+    }
+
+    /// Put a [Setting] at the given position, without checking whether it already exists, and return a mutable
+    /// reference to it. The setting keeps its comments, but takes over the blank lines above the setting it is
+    /// inserted in front of. Its line, and the line before it, are ended so that it stands on a line of its own.
+    pub(crate) fn place_setting(&mut self, index: usize, mut setting: Setting) -> &mut Setting {
+        let line_ending = self.style.line_ending;
+
+        match index.checked_sub(1) {
+            Some(previous) => self.settings[previous].line_end_mut().end_line(line_ending),
+            None => self.header.line_end_mut().end_line(line_ending),
+        }
+        setting.line_end_mut().end_line(line_ending);
+        setting.leading_whitespace.take_separator();
+        if let Some(next) = self.settings.get_mut(index) {
+            setting
+                .leading_whitespace
+                .merge_separator(next.leading_whitespace.take_separator());
+        }
+
+        self.raw_lines.item_inserted(index, self.settings.len());
+        self.settings.insert(index, setting);
+        &mut self.settings[index]
+    }
+
+    /// Sort the settings of the section with the given comparison function. The comments above each setting
+    /// move along with it, while the blank lines between settings stay where they are, as described in
+    /// [crate::AwsConfigFile#reordering]. The sort is stable, so settings which compare equal keep their order.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, SectionPath};
+    /// let mut config: AwsConfigFile = "[profile A]\n# where\nregion = us-east-1\noutput = json\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let section_path = SectionPath::try_from("profile.A").unwrap();
+    ///
+    /// config
+    ///     .section_entry(section_path)
+    ///     .or_insert()
+    ///     .sort_settings_by(|first, second| first.name().cmp(second.name()));
+    ///
+    /// assert_eq!(config.to_string(), "[profile A]\noutput = json\n# where\nregion = us-east-1\n");
+    /// ```
+    pub fn sort_settings_by(&mut self, mut compare: impl FnMut(&Setting, &Setting) -> Ordering) {
+        self.reorder_settings(|settings| settings.sort_by(|first, second| compare(first, second)))
+    }
+
+    /// Change the order of the settings with `reorder`. Every setting line is ended first, so that a setting
+    /// which used to be last can be followed by another one, and the blank lines above each setting are kept at
+    /// their position rather than moved along with it.
+    fn reorder_settings(&mut self, reorder: impl FnOnce(&mut Vec<Setting>)) {
+        if self.settings.is_empty() {
+            return;
+        }
+
+        let line_ending = self.style.line_ending;
+        let ends_with_line_break = self.settings_line_end_mut().ends_with_line_break();
+        let separators = self
+            .settings
+            .iter_mut()
+            .map(|setting| {
+                setting.line_end_mut().end_line(line_ending);
+                setting.leading_whitespace.take_separator()
+            })
+            .collect::<Vec<_>>();
+
+        reorder(&mut self.settings);
+
+        for (setting, separator) in self.settings.iter_mut().zip(separators) {
+            setting.leading_whitespace.merge_separator(separator);
+        }
+        if !ends_with_line_break {
+            self.settings_line_end_mut().remove_line_break();
+        }
+    }
+
+    /// Add a [Setting] to the end of the section, without checking whether it already exists
+    pub(crate) fn push_setting(&mut self, setting: Setting) -> &mut Setting {
+        self.end_settings_line();
+        self.raw_lines
+            .item_inserted(self.settings.len(), self.settings.len());
+        self.settings.push(setting);
+
+        #[allow(clippy::unwrap_used)]
+        // This cannot fail because the setting was just added
+        self.settings.last_mut().unwrap()
+    }
+
+    /// Remove the [Setting] with the given [SettingName], returning it if it existed. Its comments go with it.
+    /// See [crate::AwsConfigFile#comments-and-blank-lines] for the details.
+    pub fn remove_setting(&mut self, setting_name: &SettingName) -> Option<Setting> {
+        let index = self
+            .settings
+            .iter()
+            .position(|setting| setting.name() == setting_name)?;
+        let mut setting = self.settings.remove(index);
+        let separator = setting.leading_whitespace.take_separator();
+        self.raw_lines.item_removed(index);
+
+        if let Some(next) = self.settings.get_mut(index) {
+            next.leading_whitespace.merge_separator(separator);
+        }
+
+        Some(setting)
+    }
+
+    /// Remove a [NestedSetting] from under the [Setting] with the given [SettingName], returning it if it existed.
+    /// Its comments go with it. See [crate::AwsConfigFile#comments-and-blank-lines] for the details. If it was
+    /// the last setting in its block, the now empty parent [Setting] is removed as well.
+    pub fn remove_nested_setting(
+        &mut self,
+        setting_name: &SettingName,
+        nested_setting_name: &SettingName,
+    ) -> Option<NestedSetting> {
+        let ValueType::Nested(nested) = &mut self.get_setting_mut(setting_name)?.value else {
+            return None;
+        };
+
+        let removed = nested.remove(nested_setting_name)?;
+
+        if nested.is_empty() {
+            self.remove_setting(setting_name);
+        }
+
+        Some(removed)
+    }
+
+    /// Set the [Value] of a [NestedSetting] under the [Setting] with the given [SettingName]. The nested setting
+    /// is added to the end of the block if it doesn't exist, indented like its siblings, and the parent
+    /// [Setting] is created as an empty block if it doesn't exist either.
+    ///
+    /// Fails with [Error::NotNested] if the parent [Setting] holds a single value. Use
+    /// [Section::convert_to_nested] to replace that value with a block first.
+    pub fn set_nested(
+        &mut self,
+        setting_name: SettingName,
+        nested_setting_name: SettingName,
+        value: Value,
+    ) -> Result<(), Error> {
+        let line_ending = self.style.line_ending;
+        let index = match self
+            .settings
+            .iter()
+            .position(|setting| setting.name() == &setting_name)
+        {
+            Some(index) => index,
+            None => {
+                let nested = ValueType::Nested(NestedSettings::new(line_ending));
+                let setting = Setting::with_style(setting_name, nested, &self.style);
+                self.push_setting(setting);
+                self.settings.len() - 1
+            }
+        };
+
+        let setting = &mut self.settings[index];
+        match &mut setting.value {
+            ValueType::Single(_) => Err(Error::NotNested(setting.setting_name.clone())),
+            ValueType::Nested(nested) => {
+                nested.set(nested_setting_name, value, &self.style);
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the single value of the [Setting] with the given [SettingName] by an empty block of nested
+    /// settings, keeping any comment on its line. Returns the replaced [Value], or [None] if the setting doesn't
+    /// exist or is already nested.
+    pub fn convert_to_nested(&mut self, setting_name: &SettingName) -> Option<Value> {
+        let line_ending = self.style.line_ending;
+
+        self.get_setting_mut(setting_name)?
+            .convert_to_nested(line_ending)
+    }
+
+    /// Read the settings of this section into a user-defined type, with the `serde` feature. Each setting is a
+    /// field of the type, and a block of nested settings is a nested struct or a map. Values are converted to
+    /// whatever the field asks for: `true` and `false` become bools, numbers become integers or floats, a
+    /// comma-separated value becomes a list, and the name of a unit variant becomes an enum. Settings which
+    /// are missing are [None] for optional fields.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, SectionPath};
+    /// #[derive(serde::Deserialize)]
+    /// struct S3 {
+    ///     max_concurrent_requests: u32,
+    ///     use_accelerate_endpoint: bool,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct MyProfile {
+    ///     region: String,
+    ///     max_attempts: Option<u8>,
+    ///     s3: S3,
+    /// }
+    ///
+    /// let config: AwsConfigFile = "[profile A]\nregion = us-east-1\ns3 =\n  max_concurrent_requests = 20\n  use_accelerate_endpoint = true\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let section = config.get_section(&SectionPath::try_from("profile.A").unwrap()).unwrap();
+    ///
+    /// let profile = section.deserialize::<MyProfile>().unwrap();
+    ///
+    /// assert_eq!(profile.region, "us-east-1");
+    /// assert_eq!(profile.max_attempts, None);
+    /// assert_eq!(profile.s3.max_concurrent_requests, 20);
+    /// assert!(profile.s3.use_accelerate_endpoint);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, D: serde::Deserialize<'de>>(&'de self) -> Result<D, Error> {
+        D::deserialize(SectionDeserializer::new(self))
+    }
+
+    /// Write the fields of a user-defined type to this section, with the `serde` feature, in the form which
+    /// [Section::deserialize] reads. Only the settings whose values differ are changed, so the formatting and
+    /// comments of the others stay as they are, and settings which aren't fields of the type are left alone.
+    /// Values are compared as the type of their field, so `True` is kept for a bool which is `true`, and
+    /// `1.50` for a number which is `1.5`.
+    /// A field which is [None] or an empty list removes its setting. Nothing is changed, and
+    /// [Error::Serde] is returned, if any of the fields can't be written to a config file, such as a string
+    /// which spans several lines.
+    ///
+    /// ```
+    /// # use aws_config_mod::{AwsConfigFile, SectionPath};
+    /// #[derive(serde::Serialize)]
+    /// struct MyProfile {
+    ///     region: String,
+    ///     max_attempts: Option<u8>,
+    /// }
+    ///
+    /// let mut config: AwsConfigFile = "[profile A]\nregion=us-east-1 # home\noutput=json\nmax_attempts=3\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let section = config.sections_mut().next().unwrap();
+    ///
+    /// section
+    ///     .update_from(&MyProfile { region: "eu-west-1".to_string(), max_attempts: None })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.to_string(), "[profile A]\nregion=eu-west-1 # home\noutput=json\n");
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn update_from<S: serde::Serialize + ?Sized>(&mut self, value: &S) -> Result<(), Error> {
+        update_section(self, value)
+    }
+
+    /// Rewrite the spacing, indentation, blank lines and line endings of this section as described by the
+    /// [FormatOptions], with the given number of blank lines above it, and sort its settings if they ask for
+    /// it. Settings added to the section afterwards follow the same style.
+    pub(crate) fn format(&mut self, options: &FormatOptions, blank_lines_before: usize) {
+        let line_ending = self.style.line_ending;
+
+        if options.sort_settings {
+            self.sort_settings_by(|first, second| first.name().cmp(second.name()));
+        }
+
+        self.leading_whitespace
+            .format_lines(Some(blank_lines_before), "", line_ending);
+        let header_line_end = self.header.line_end_mut();
+        header_line_end.end_line(line_ending);
+        header_line_end.format_line_end(options.comment_spacing(), line_ending);
+        self.settings
+            .iter_mut()
+            .for_each(|setting| setting.format(options, line_ending));
+        if let Some(first) = self.settings.first_mut() {
+            first.leading_whitespace.set_blank_lines(0, line_ending);
+        }
+        self.raw_lines.format("", line_ending);
+        self.trailing_whitespace
+            .format_lines(Some(0), "", line_ending);
+
+        self.style = options.style(line_ending);
+    }
+
+    /// Make sure that the last line of this section, including its trailing whitespace, ends with a line break
+    /// so that more content can follow it.
+    pub(crate) fn end_last_line(&mut self) {
+        let line_ending = self.style.line_ending;
+
+        self.last_line_end_mut().end_line(line_ending)
+    }
+
+    /// Make sure that the line of the last setting, or of the header if there are no settings, ends with a
+    /// line break so that a new setting can follow it.
+    fn end_settings_line(&mut self) {
+        let line_ending = self.style.line_ending;
+
+        self.settings_line_end_mut().end_line(line_ending)
+    }
+
+    /// The whitespace holding the line ending of the last line of this section, including its trailing
+    /// whitespace and the lines kept by lenient parsing after its settings
+    pub(crate) fn last_line_end_mut(&mut self) -> &mut Whitespace {
+        if !self.trailing_whitespace.is_empty() {
+            return &mut self.trailing_whitespace;
+        }
+
+        match self.raw_lines.line_end_after_mut(self.settings.len()) {
+            Some(line_end) => line_end,
+            None => match self.settings.last_mut() {
+                Some(setting) => setting.line_end_mut(),
+                None => self.header.line_end_mut(),
+            },
+        }
+    }
+
+    /// The whitespace holding the line ending of the line of the last setting, or of the header if there are
+    /// no settings
+    fn settings_line_end_mut(&mut self) -> &mut Whitespace {
+        match self.settings.last_mut() {
+            Some(setting) => setting.line_end_mut(),
+            None => self.header.line_end_mut(),
         }
     }
 }
@@ -128,17 +681,9 @@ where
     T: Display,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}{}{}{}",
-            self.leading_whitespace,
-            self.header,
-            self.settings
-                .iter()
-                .map(Setting::to_string)
-                .collect::<String>(),
-            self.trailing_whitespace,
-        )
+        write!(f, "{}{}", self.leading_whitespace, self.header)?;
+        self.raw_lines.write_with(f, &self.settings)?;
+        write!(f, "{}", self.trailing_whitespace)
     }
 }
 
@@ -149,21 +694,391 @@ where
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (next, leading_whitespace) = Whitespace::parse(input)?;
+        let (next, leading_whitespace) = Whitespace::parse_lines(input)?;
         let (next, header) = T::parse(next)?;
         let (next, settings) = many0(Setting::parse)(next)?;
-        let (next, trailing_whitespace) = Whitespace::parse(next)?;
+        let (next, trailing_whitespace) = Whitespace::parse_lines(next)?;
         let section = Self {
             header,
             settings,
+            raw_lines: RawLines::default(),
             leading_whitespace,
             trailing_whitespace,
+            style: Style {
+                line_ending: LineEnding::detect(&input[..input.len() - next.len()]),
+                ..Default::default()
+            },
         };
 
         Ok((next, section))
     }
 }
 
+/// The error type produced while parsing the sections of a file
+pub(crate) type SectionError<'a> = nom::Err<VerboseError<&'a str>>;
+
+/// Parse the sections of a whole file line by line, returning the lines before the first section which could
+/// not be parsed, the whitespace before the first section, the sections themselves and the whitespace after
+/// the last section.
+///
+/// Blank lines and comments between two sections are split up: the comment lines directly above a header,
+/// along with the blank lines above those comments, belong to the [Section] of that header, and the rest
+/// belongs to the [Section] before it. The same split decides which lines at the end of the file belong to
+/// the file rather than to the last section.
+///
+/// Whenever a line can neither start a new section nor continue the current one, the error is handed
+/// to `recover`. Returning the error stops parsing. Returning `Ok` keeps the line verbatim as a [RawLine] of
+/// the current section, or of the file before the first section, and carries on with the next line. After a
+/// header that fails to parse, the lines up to the next valid header are kept verbatim as well, since they
+/// don't belong to any section.
+pub(crate) fn parse_sections<'a, T>(
+    input: &'a str,
+    mut recover: impl FnMut(SectionError<'a>) -> Result<(), SectionError<'a>>,
+) -> ParserOutput<'a, (RawLines, Whitespace, Vec<Section<T>>, Whitespace)>
+where
+    T: Parsable<'a, Output = T> + Default,
+{
+    let mut next = input;
+    let mut file_raw_lines = RawLines::default();
+    let mut leading_whitespace = Whitespace::default();
+    let mut sections: Vec<Section<T>> = vec![];
+    let mut pending = String::new();
+    let mut raw_lines = vec![];
+    let mut in_unparsed_section = false;
+
+    loop {
+        let (rest, whitespace) = Whitespace::parse_lines(next)?;
+        pending.push_str(&whitespace);
+        next = rest;
+
+        if next.is_empty() {
+            break;
+        }
+
+        let content = next.trim_start_matches([' ', '\t']);
+
+        let error = if content.starts_with('[') {
+            match T::parse(content) {
+                Ok((rest, header)) => {
+                    attach_raw_lines(&mut file_raw_lines, &mut sections, &mut raw_lines);
+                    pending.push_str(&next[..next.len() - content.len()]);
+                    let (detached, attached) =
+                        Whitespace(std::mem::take(&mut pending)).split_attached();
+                    let owner = match sections.last_mut() {
+                        Some(section) => &mut section.trailing_whitespace,
+                        None => &mut leading_whitespace,
+                    };
+                    owner.0.push_str(&detached);
+                    sections.push(Section {
+                        leading_whitespace: attached,
+                        header,
+                        settings: vec![],
+                        raw_lines: RawLines::default(),
+                        trailing_whitespace: Whitespace::default(),
+                        style: Style::default(),
+                    });
+                    in_unparsed_section = false;
+                    next = rest;
+                    continue;
+                }
+                Err(error) => {
+                    in_unparsed_section = true;
+                    error
+                }
+            }
+        } else {
+            match sections.last_mut() {
+                Some(section) if !in_unparsed_section => {
+                    match parse_section_line(section, next, &mut pending, &mut raw_lines) {
+                        Ok(rest) => {
+                            next = rest;
+                            continue;
+                        }
+                        Err(error) => error,
+                    }
+                }
+                _ => nom::Err::Error(VerboseError {
+                    errors: vec![(next, VerboseErrorKind::Context(Expected::Header.label()))],
+                }),
+            }
+        };
+
+        recover(error)?;
+
+        let line_length = next.find('\n').map(|i| i + 1).unwrap_or(next.len());
+        let (line, rest) = next.split_at(line_length);
+        raw_lines.push(RawLine::new(
+            0,
+            Whitespace(std::mem::take(&mut pending)),
+            line,
+        ));
+        next = rest;
+    }
+
+    attach_raw_lines(&mut file_raw_lines, &mut sections, &mut raw_lines);
+    let (detached, trailing_whitespace) = Whitespace(pending).split_attached();
+
+    match sections.last_mut() {
+        Some(section) => {
+            section.trailing_whitespace.0.push_str(&detached);
+            Ok((
+                next,
+                (
+                    file_raw_lines,
+                    leading_whitespace,
+                    sections,
+                    trailing_whitespace,
+                ),
+            ))
+        }
+        None => {
+            leading_whitespace.0.push_str(&detached);
+            leading_whitespace.0.push_str(&trailing_whitespace);
+            Ok((
+                next,
+                (
+                    file_raw_lines,
+                    leading_whitespace,
+                    sections,
+                    Whitespace::default(),
+                ),
+            ))
+        }
+    }
+}
+
+/// Hand the `raw_lines` read since the last parsed line to their owner: the end of the last section, or the
+/// file if they came before the first section
+fn attach_raw_lines<T>(
+    file_raw_lines: &mut RawLines,
+    sections: &mut [Section<T>],
+    raw_lines: &mut Vec<RawLine>,
+) {
+    let raw_lines = std::mem::take(raw_lines);
+
+    match sections.last_mut() {
+        Some(section) => section.raw_lines.append(raw_lines, section.settings.len()),
+        None => file_raw_lines.append(raw_lines, 0),
+    }
+}
+
+/// Parse a line which continues `section`, adding it to the section along with the `pending` whitespace
+/// and the `raw_lines` before it. An indented line after a block of nested settings continues that block,
+/// even if lines which could not be parsed came in between.
+fn parse_section_line<'a, T>(
+    section: &mut Section<T>,
+    input: &'a str,
+    pending: &mut String,
+    raw_lines: &mut Vec<RawLine>,
+) -> Result<&'a str, SectionError<'a>> {
+    if input.starts_with([' ', '\t']) {
+        if let Some(ValueType::Nested(nested)) = section
+            .settings
+            .last_mut()
+            .map(|setting| &mut setting.value)
+        {
+            if let Ok((rest, mut nested_setting)) = NestedSetting::parse(input) {
+                let mut leading_whitespace = std::mem::take(&mut nested.trailing_whitespace);
+                if let Some(first) = raw_lines.first_mut() {
+                    leading_whitespace.0.push_str(&first.leading_whitespace);
+                    first.leading_whitespace = std::mem::take(&mut leading_whitespace);
+                }
+                nested
+                    .raw_lines
+                    .append(std::mem::take(raw_lines), nested.len());
+                leading_whitespace.0.push_str(&std::mem::take(pending));
+                leading_whitespace
+                    .0
+                    .push_str(&nested_setting.leading_whitespace);
+                nested_setting.leading_whitespace = leading_whitespace;
+                nested.nested_settings.push(nested_setting);
+                return Ok(rest);
+            }
+        }
+    }
+
+    let (rest, mut setting) = Setting::parse(input)?;
+    section
+        .raw_lines
+        .append(std::mem::take(raw_lines), section.settings.len());
+    pending.push_str(&setting.leading_whitespace);
+    setting.leading_whitespace = Whitespace(std::mem::take(pending));
+    section.settings.push(setting);
+
+    Ok(rest)
+}
+
+/// Insert a [Section] into the sections of a file at the given position, and return a mutable reference to it.
+/// The section keeps its comments, but takes over the blank lines above the section it is inserted in front
+/// of, which is then separated from it as the [Style] of the new section says. `has_content_above` tells
+/// whether anything precedes the first section, in which case a section added at the top is separated from
+/// that as well.
+pub(crate) fn insert_section_at<T: Header>(
+    sections: &mut Vec<Section<T>>,
+    index: usize,
+    mut section: Section<T>,
+    has_content_above: bool,
+) -> &mut Section<T> {
+    let blank_lines = section.style.blank_lines_between_sections;
+    let line_ending = section.style.line_ending;
+
+    if let Some(previous) = index
+        .checked_sub(1)
+        .and_then(|index| sections.get_mut(index))
+    {
+        previous.end_last_line();
+    }
+
+    section.leading_whitespace.take_separator();
+    match sections.get_mut(index) {
+        Some(next) => {
+            section.end_last_line();
+            section
+                .leading_whitespace
+                .merge_separator(next.leading_whitespace.take_separator());
+            next.leading_whitespace
+                .set_blank_lines(blank_lines, line_ending);
+        }
+        None if index > 0 || has_content_above => section.set_blank_lines_before(blank_lines),
+        None => (),
+    }
+
+    sections.insert(index, section);
+    &mut sections[index]
+}
+
+/// Change the order of the sections of a file with `reorder`, as described in
+/// [crate::AwsConfigFile#reordering]. Every section is ended with a line break first, so that a section which
+/// used to be last can be followed by another one, and the blank lines above each section are kept at their
+/// position rather than moved along with it.
+pub(crate) fn reorder_sections<T: Header>(
+    sections: &mut Vec<Section<T>>,
+    reorder: impl FnOnce(&mut Vec<Section<T>>),
+) {
+    let ends_with_line_break = sections
+        .last_mut()
+        .is_none_or(|section| section.last_line_end_mut().ends_with_line_break());
+    let separators = sections
+        .iter_mut()
+        .map(|section| {
+            section.end_last_line();
+            section.leading_whitespace.take_separator()
+        })
+        .collect::<Vec<_>>();
+
+    reorder(sections);
+
+    for (section, separator) in sections.iter_mut().zip(separators) {
+        section.leading_whitespace.merge_separator(separator);
+    }
+    if let Some(last) = sections.last_mut().filter(|_| !ends_with_line_break) {
+        last.last_line_end_mut().remove_line_break();
+    }
+}
+
+/// Move the [Section] at position `from` to position `to` within the sections of a file, shifting the sections
+/// in between, and return a mutable reference to it. Returns [None] if either position is out of range.
+pub(crate) fn move_section<T: Header>(
+    sections: &mut Vec<Section<T>>,
+    from: usize,
+    to: usize,
+) -> Option<&mut Section<T>> {
+    if from >= sections.len() || to >= sections.len() {
+        return None;
+    }
+
+    reorder_sections(sections, |sections| {
+        let section = sections.remove(from);
+        sections.insert(to, section);
+    });
+
+    sections.get_mut(to)
+}
+
+/// Rewrite the lines of a file as described by the [FormatOptions], apart from sorting it. `raw_lines` and
+/// `leading_whitespace` are the lines before the first of its `sections`, and `trailing_whitespace` holds the
+/// lines after the last one.
+pub(crate) fn format_file<T: Header>(
+    raw_lines: &mut RawLines,
+    leading_whitespace: &mut Whitespace,
+    sections: &mut [Section<T>],
+    trailing_whitespace: &mut Whitespace,
+    options: &FormatOptions,
+    line_ending: LineEnding,
+) {
+    leading_whitespace.format_lines(Some(0), "", line_ending);
+    raw_lines.format("", line_ending);
+
+    let head_is_empty = leading_whitespace.is_empty() && raw_lines.is_empty();
+    for (index, section) in sections.iter_mut().enumerate() {
+        let blank_lines = match index == 0 && head_is_empty {
+            true => 0,
+            false => options.blank_lines_between_sections,
+        };
+        section.format(options, blank_lines);
+    }
+
+    trailing_whitespace.format_lines(Some(0), "", line_ending);
+
+    if options.trailing_newline {
+        end_file_line(raw_lines, leading_whitespace, sections, line_ending);
+    } else if let Some(line_end) =
+        file_line_end_mut(raw_lines, leading_whitespace, sections, trailing_whitespace)
+    {
+        line_end.remove_line_break();
+    }
+}
+
+/// The whitespace holding the line ending of the last line of a file, if the file isn't empty. The parts of the
+/// file are the same as for [format_file].
+fn file_line_end_mut<'a, T: Header>(
+    raw_lines: &'a mut RawLines,
+    leading_whitespace: &'a mut Whitespace,
+    sections: &'a mut [Section<T>],
+    trailing_whitespace: &'a mut Whitespace,
+) -> Option<&'a mut Whitespace> {
+    if !trailing_whitespace.is_empty() {
+        return Some(trailing_whitespace);
+    }
+
+    match sections.last_mut() {
+        Some(section) => Some(section.last_line_end_mut()),
+        None if !leading_whitespace.is_empty() => Some(leading_whitespace),
+        None => raw_lines.line_end_after_mut(0),
+    }
+}
+
+/// Make sure that the last line of a file before the whitespace at its end ends with a line break, so that a
+/// new section can follow it. The parts of the file are the same as for [format_file].
+pub(crate) fn end_file_line<T: Header>(
+    raw_lines: &mut RawLines,
+    leading_whitespace: &mut Whitespace,
+    sections: &mut [Section<T>],
+    line_ending: LineEnding,
+) {
+    match sections.last_mut() {
+        Some(section) => section.end_last_line(),
+        None if !leading_whitespace.is_empty() => leading_whitespace.end_line(line_ending),
+        None => {
+            if let Some(line_end) = raw_lines.line_end_after_mut(0) {
+                line_end.end_line(line_ending)
+            }
+        }
+    }
+}
+
+/// Remove the section at `index`, following the policy described in [crate::AwsConfigFile#comments-and-blank-lines]
+pub(crate) fn remove_section_at<T>(sections: &mut Vec<Section<T>>, index: usize) -> Section<T> {
+    let mut section = sections.remove(index);
+    let separator = section.leading_whitespace.take_separator();
+
+    if let Some(next) = sections.get_mut(index) {
+        next.leading_whitespace.merge_separator(separator);
+    }
+
+    section
+}
+
 #[cfg(test)]
 mod test {
     use super::Section;
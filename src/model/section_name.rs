@@ -3,10 +3,11 @@
 //! that is the section name. If two items are contained within the square brackets, the second item is
 //! the section name and the first is the section type.
 
-use crate::lexer::{to_owned_input, Parsable, ParserOutput};
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
 use nom::{
     branch::alt, bytes::complete::tag, character::complete::alphanumeric1, combinator::recognize,
-    multi::many1_count,
+    error::context, multi::many1_count,
 };
 use std::{fmt::Display, ops::Deref, str::FromStr};
 
@@ -45,10 +46,7 @@ impl FromStr for SectionName {
     type Err = crate::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::parse(s)
-            .map(|a| a.1)
-            .map_err(to_owned_input)
-            .map_err(crate::Error::from)
+        Ok(parse_complete::<Self>(s)?)
     }
 }
 
@@ -56,8 +54,10 @@ impl<'a> Parsable<'a> for SectionName {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (next, heading_name) =
-            recognize(many1_count(alt((alphanumeric1, tag("_"), tag("-")))))(input)?;
+        let (next, heading_name) = context(
+            Expected::SectionName.label(),
+            recognize(many1_count(alt((alphanumeric1, tag("_"), tag("-"))))),
+        )(input)?;
 
         Ok((next, Self(heading_name.to_string())))
     }
@@ -2,14 +2,28 @@
 //! and are used to identify specific sections within a configuration file.
 
 use super::{SectionName, SectionType};
-use crate::lexer::{to_owned_input, Parsable};
-use nom::{bytes::complete::tag, combinator::eof};
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{eof, opt, peek, verify},
+    error::context,
+    sequence::{preceded, terminated},
+};
+use std::fmt::Display;
 
 // TODO: need to get rid of SectionPath and just have a generic path that makes no assumptions
 // about what each part means. Whatever you give it to makes those decisions and then returns
 // errors as necessary
 
-/// Represents the path to a specific [crate::Section] in a config file.
+/// Represents the path to a specific [crate::Section] in a config file. Its string form is the section type
+/// followed by the section name, separated by a '.', such as `profile.A`. Sections of type
+/// [SectionType::Plugins] and [SectionType::Preview] usually have no name, so their path can be just the
+/// section type. The default profile can also be written as just `default`.
+///
+/// To address a [crate::Section], [crate::Setting] or [crate::NestedSetting] with a single type, use
+/// [crate::ConfigPath].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SectionPath {
     /// The type of the [crate::Section] to lookup. For example, could be 'profile' or 'sso-session', etc
@@ -24,9 +38,7 @@ impl TryFrom<&str> for SectionPath {
     type Error = crate::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let (_, section_path) = Self::parse(value).map_err(to_owned_input)?;
-
-        Ok(section_path)
+        Ok(parse_complete::<Self>(value)?)
     }
 }
 
@@ -34,10 +46,8 @@ impl TryFrom<(&str, &str)> for SectionPath {
     type Error = crate::Error;
 
     fn try_from((section_type, section_name): (&str, &str)) -> Result<Self, Self::Error> {
-        let (next, section_type) = SectionType::parse(section_type).map_err(to_owned_input)?;
-        eof(next).map_err(to_owned_input)?;
-        let (next, section_name) = SectionName::parse(section_name).map_err(to_owned_input)?;
-        eof(next).map_err(to_owned_input)?;
+        let section_type = parse_complete::<SectionType>(section_type)?;
+        let section_name = parse_complete::<SectionName>(section_name)?;
 
         let config_path = (section_type, Some(section_name)).into();
         Ok(config_path)
@@ -48,8 +58,7 @@ impl TryFrom<(SectionType, &str)> for SectionPath {
     type Error = crate::Error;
 
     fn try_from((section_type, section_name): (SectionType, &str)) -> Result<Self, Self::Error> {
-        let (next, section_name) = SectionName::parse(section_name).map_err(to_owned_input)?;
-        eof(next).map_err(to_owned_input)?;
+        let section_name = parse_complete::<SectionName>(section_name)?;
 
         let config_path = (section_type, Some(section_name)).into();
         Ok(config_path)
@@ -90,13 +99,41 @@ impl From<(SectionType, SectionName)> for SectionPath {
     }
 }
 
+impl Display for SectionPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.section_name {
+            Some(section_name) => write!(f, "{}.{}", self.section_type, section_name),
+            None => write!(f, "{}", self.section_type),
+        }
+    }
+}
+
 impl<'a> Parsable<'a> for SectionPath {
     type Output = Self;
 
-    // TODO: fix this to handle default profile correctly
     fn parse(input: &'a str) -> crate::lexer::ParserOutput<'a, Self::Output> {
+        if let Ok((next, default)) = default_profile(input) {
+            let section_path = Self {
+                section_type: SectionType::Profile,
+                section_name: Some(SectionName(default.to_string())),
+            };
+
+            return Ok((next, section_path));
+        }
+
         let (next, section_type) = SectionType::parse(input)?;
-        let (next, _) = tag(".")(next)?;
+
+        if let SectionType::Plugins | SectionType::Preview = section_type {
+            let (next, section_name) = opt(preceded(tag("."), SectionName::parse))(next)?;
+            let section_path = Self {
+                section_type,
+                section_name,
+            };
+
+            return Ok((next, section_path));
+        }
+
+        let (next, _) = context(Expected::PathSeparator.label(), tag("."))(next)?;
 
         let (next, section_name) = SectionName::parse(next)?;
 
@@ -110,3 +147,23 @@ impl<'a> Parsable<'a> for SectionPath {
         Ok((next, config_path))
     }
 }
+
+/// Parses the path to a section of a type which usually has no name, such as `preview`, without looking for a
+/// name after it. Paths to settings use this when the section name would leave too few parts for the settings.
+pub(crate) fn unnamed_section_path(input: &str) -> crate::lexer::ParserOutput<'_, SectionPath> {
+    let (next, section_type) = verify(SectionType::parse, |section_type| {
+        matches!(section_type, SectionType::Plugins | SectionType::Preview)
+    })(input)?;
+
+    let section_path = SectionPath {
+        section_type,
+        section_name: None,
+    };
+
+    Ok((next, section_path))
+}
+
+/// Matches the short form of the path to the default profile, `default`, which can stand in for `profile.default`
+fn default_profile(input: &str) -> crate::lexer::ParserOutput<'_, &str> {
+    terminated(tag("default"), peek(alt((eof, tag(".")))))(input)
+}
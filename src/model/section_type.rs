@@ -1,8 +1,12 @@
 //! Contains items related to parsing section types.
 
-use crate::lexer::{Parsable, ParserOutput};
-use nom::{branch::alt, bytes::complete::tag, character::complete::alphanumeric1, combinator::map};
-use std::fmt::Display;
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
+use nom::{
+    branch::alt, bytes::complete::tag, character::complete::alphanumeric1, combinator::map,
+    error::context,
+};
+use std::{fmt::Display, str::FromStr};
 
 /// Represents the various section types of an AWS config file. If an unknown section type is
 /// encountered, rather than failing it's value is collected under [SectionType::Other]
@@ -86,17 +90,28 @@ impl Display for SectionType {
     }
 }
 
+impl FromStr for SectionType {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse_complete::<Self>(s)?)
+    }
+}
+
 impl<'a> Parsable<'a> for SectionType {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        alt((
-            map(tag(Self::PROFILE), |_| Self::Profile),
-            map(tag(Self::SSO_SESSION), |_| Self::SsoSession),
-            map(tag(Self::SERVICES), |_| Self::Services),
-            map(tag(Self::PLUGINS), |_| Self::Plugins),
-            map(tag(Self::PREVIEW), |_| Self::Preview),
-            map(alphanumeric1, |other: &str| Self::Other(other.to_string())),
-        ))(input)
+        context(
+            Expected::SectionType.label(),
+            alt((
+                map(tag(Self::PROFILE), |_| Self::Profile),
+                map(tag(Self::SSO_SESSION), |_| Self::SsoSession),
+                map(tag(Self::SERVICES), |_| Self::Services),
+                map(tag(Self::PLUGINS), |_| Self::Plugins),
+                map(tag(Self::PREVIEW), |_| Self::Preview),
+                map(alphanumeric1, |other: &str| Self::Other(other.to_string())),
+            )),
+        )(input)
     }
 }
@@ -0,0 +1,330 @@
+//! Contains the [serde] implementations which write the document model as a plain map, leaving out comments and
+//! formatting. A config file becomes a map from [SectionType] to [SectionName] to [SettingName] to a value, which
+//! is a string or a map of nested settings:
+//!
+//! ```json
+//! {
+//!     "profile": {
+//!         "A": { "region": "us-east-1", "s3": { "max_queue_size": "1000" } }
+//!     },
+//!     "plugins": { "cli_legacy_plugin_path": "/usr/lib/python3/site-packages" }
+//! }
+//! ```
+//!
+//! Sections without a name, such as `[plugins]` and `[preview]`, map straight to their settings. A credentials
+//! file has no section types, so it is a map from [SectionName] to its settings.
+
+use super::{
+    diff::{nested_settings_by_name, sections_by_path, settings_by_name},
+    header::Header,
+    nested_settings::NestedSettings,
+    style::Style,
+    AwsConfigFile, AwsCredentialsFile, LineEnding, Section, SectionName, SectionPath, SectionType,
+    Setting, SettingName, Value, ValueType,
+};
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+};
+
+impl Serialize for ValueType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ValueType::Single(value) => value.serialize(serializer),
+            ValueType::Nested(nested) => nested.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for NestedSettings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nested_settings = nested_settings_by_name(self);
+
+        let mut map = serializer.serialize_map(Some(nested_settings.len()))?;
+        for (name, nested) in nested_settings {
+            map.serialize_entry(name, nested.value())?;
+        }
+        map.end()
+    }
+}
+
+impl<T: Header> Serialize for Section<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let settings = settings_by_name(self);
+
+        let mut map = serializer.serialize_map(Some(settings.len()))?;
+        for (name, setting) in settings {
+            map.serialize_entry(name, setting.value())?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for AwsConfigFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let sections = sections_by_path(self.visible_sections());
+
+        let mut section_types = Vec::<&SectionType>::new();
+        for (path, _) in &sections {
+            if !section_types.contains(&&path.section_type) {
+                section_types.push(&path.section_type);
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(section_types.len()))?;
+        for section_type in section_types {
+            let mut of_type = sections
+                .iter()
+                .filter(|(path, _)| path.section_type == *section_type);
+
+            match is_unnamed(section_type) {
+                #[allow(clippy::unwrap_used)]
+                // This cannot fail because the type was taken from one of these sections
+                true => map.serialize_entry(section_type, of_type.next().unwrap().1)?,
+                false => {
+                    let named = of_type
+                        .filter_map(|(path, section)| Some((path.section_name.as_ref()?, *section)))
+                        .collect::<Vec<_>>();
+                    map.serialize_entry(section_type, &NamedSections(named))?
+                }
+            }
+        }
+        map.end()
+    }
+}
+
+impl Serialize for AwsCredentialsFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let profiles = sections_by_path(self.profiles());
+
+        let mut map = serializer.serialize_map(Some(profiles.len()))?;
+        for (path, profile) in profiles {
+            if let Some(profile_name) = &path.section_name {
+                map.serialize_entry(profile_name, profile)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// The sections of one [SectionType], which are written as a map from their [SectionName] to their settings
+struct NamedSections<'a, T>(Vec<(&'a SectionName, &'a Section<T>)>);
+
+impl<T: Header> Serialize for NamedSections<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (section_name, section) in &self.0 {
+            map.serialize_entry(section_name, section)?;
+        }
+        map.end()
+    }
+}
+
+/// Whether sections of this type have no name, such as `[plugins]`
+fn is_unnamed(section_type: &SectionType) -> bool {
+    matches!(section_type, SectionType::Plugins | SectionType::Preview)
+}
+
+impl<'de> Deserialize<'de> for ValueType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueTypeVisitor)
+    }
+}
+
+/// Reads a [ValueType] from a string, which becomes a single [Value], or from a map, which becomes a block of
+/// nested settings. Numbers and booleans are taken as their string form. A string which couldn't be read back
+/// as the same value, such as one which is empty, spans several lines or holds a comment, is rejected.
+struct ValueTypeVisitor;
+
+impl<'de> Visitor<'de> for ValueTypeVisitor {
+    type Value = ValueType;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a value or a map of nested settings")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map(ValueType::Single).map_err(E::custom)
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let style = Style::default();
+        let mut nested = NestedSettings::new(LineEnding::default());
+
+        while let Some((name, value)) = map.next_entry::<SettingName, ScalarValue>()? {
+            nested.set(name, value.0, &style);
+        }
+
+        Ok(ValueType::Nested(nested))
+    }
+}
+
+/// A [Value] which may also be written as a number or a boolean
+struct ScalarValue(Value);
+
+impl<'de> Deserialize<'de> for ScalarValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match ValueType::deserialize(deserializer)? {
+            ValueType::Single(value) => Ok(Self(value)),
+            ValueType::Nested(_) => Err(serde::de::Error::custom(
+                "nested settings can't be nested any further",
+            )),
+        }
+    }
+}
+
+/// The entries of a map in the order they were read, rather than in a map type which may reorder them. A map
+/// which repeats a key is rejected, as each key would become a section or setting of its own.
+struct Entries<K, V>(Vec<(K, V)>);
+
+/// The settings of a section in the order they were read
+type Settings = Entries<SettingName, ValueType>;
+
+impl<'de, K, V> Deserialize<'de> for Entries<K, V>
+where
+    K: Deserialize<'de> + PartialEq + Display,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(EntriesVisitor(PhantomData))
+    }
+}
+
+/// Reads the entries of a map into [Entries]
+struct EntriesVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for EntriesVisitor<K, V>
+where
+    K: Deserialize<'de> + PartialEq + Display,
+    V: Deserialize<'de>,
+{
+    type Value = Entries<K, V>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries: Vec<(K, V)> = vec![];
+        while let Some((key, value)) = map.next_entry()? {
+            if entries.iter().any(|(existing, _)| *existing == key) {
+                return Err(duplicate_key(&key));
+            }
+            entries.push((key, value));
+        }
+
+        Ok(Entries(entries))
+    }
+}
+
+/// The error for a map which repeats the given key
+fn duplicate_key<E: serde::de::Error>(key: &impl Display) -> E {
+    E::custom(format!("duplicate key '{key}'"))
+}
+
+/// Add the settings to the section, formatted in the style of the section
+fn fill_section<T: Header>(section: &mut Section<T>, settings: Settings) {
+    for (setting_name, value) in settings.0 {
+        match value {
+            ValueType::Single(value) => section.set(setting_name, value),
+            ValueType::Nested(nested) => {
+                let mut block = NestedSettings::new(section.style.line_ending);
+                for nested in nested.iter() {
+                    block.set(
+                        nested.name().clone(),
+                        nested.value().clone(),
+                        &section.style,
+                    );
+                }
+
+                let block = ValueType::Nested(block);
+                let setting = Setting::with_style(setting_name, block, &section.style);
+                section.push_setting(setting);
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AwsConfigFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ConfigFileVisitor)
+    }
+}
+
+/// Reads an [AwsConfigFile] from a map of section types, as described in the module documentation
+struct ConfigFileVisitor;
+
+impl<'de> Visitor<'de> for ConfigFileVisitor {
+    type Value = AwsConfigFile;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map from section types to sections")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut config = AwsConfigFile::default();
+        let mut section_types = vec![];
+
+        while let Some(section_type) = map.next_key::<SectionType>()? {
+            if section_types.contains(&section_type) {
+                return Err(duplicate_key(&section_type));
+            }
+            section_types.push(section_type.clone());
+
+            if is_unnamed(&section_type) {
+                let settings = map.next_value::<Settings>()?;
+                let section_path = SectionPath {
+                    section_type,
+                    section_name: None,
+                };
+                fill_section(config.insert_section(&section_path), settings);
+                continue;
+            }
+
+            let sections = map.next_value::<Entries<SectionName, Settings>>()?;
+            for (section_name, settings) in sections.0 {
+                let section_path = SectionPath {
+                    section_type: section_type.clone(),
+                    section_name: Some(section_name),
+                };
+                fill_section(config.insert_section(&section_path), settings);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl<'de> Deserialize<'de> for AwsCredentialsFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let profiles = Entries::<SectionName, Settings>::deserialize(deserializer)?;
+
+        let mut credentials = AwsCredentialsFile::new();
+        for (profile_name, settings) in profiles.0 {
+            fill_section(credentials.insert_profile(profile_name), settings);
+        }
+
+        Ok(credentials)
+    }
+}
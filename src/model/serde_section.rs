@@ -0,0 +1,837 @@
+//! Contains a [serde] [Deserializer] and [Serializer] which convert a [Section] to and from a user-defined type,
+//! as done by [Section::deserialize] and [Section::update_from]. The settings of the section are the fields of
+//! the type, and a block of nested settings is a nested type. Since every value in a config file is a string,
+//! values are converted to whatever the type asks for, such as a bool, a number or an enum.
+
+use super::{
+    header::Header, nested_settings::NestedSettings, Section, SettingName, Value, ValueType,
+};
+use crate::Error;
+use serde::{
+    de::{
+        value::{BorrowedStrDeserializer, SeqDeserializer},
+        DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+    ser::{
+        Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple,
+        SerializeTupleStruct,
+    },
+    Deserializer, Serialize, Serializer,
+};
+use std::fmt::Display;
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::Serde(message.to_string())
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::Serde(message.to_string())
+    }
+}
+
+/// A value in a [Section] as seen by the [Deserializer]
+#[derive(Debug, Clone, Copy)]
+enum ValueRef<'de> {
+    /// A single value
+    Single(&'de str),
+
+    /// A block of nested settings
+    Nested(&'de NestedSettings),
+}
+
+/// Reads the settings of a [Section] as a map, which can be deserialized into a struct
+#[derive(Debug)]
+pub(crate) struct SectionDeserializer<'de> {
+    /// The settings of the section, by name
+    settings: Vec<(&'de SettingName, ValueRef<'de>)>,
+}
+
+impl<'de> SectionDeserializer<'de> {
+    /// Create a deserializer over the settings of the section. If a setting appears twice, the first one is used.
+    pub(crate) fn new<T: Header>(section: &'de Section<T>) -> Self {
+        let mut settings = Vec::<(&SettingName, ValueRef)>::new();
+        for setting in section.settings() {
+            if settings.iter().all(|(name, _)| *name != setting.name()) {
+                let value = match setting.value() {
+                    ValueType::Single(value) => ValueRef::Single(value),
+                    ValueType::Nested(nested) => ValueRef::Nested(nested),
+                };
+                settings.push((setting.name(), value));
+            }
+        }
+
+        Self { settings }
+    }
+}
+
+impl<'de> Deserializer<'de> for SectionDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(Settings::new(None, self.settings))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Hands the settings of a section, or of a block of nested settings, to a [Visitor] one by one
+struct Settings<'de> {
+    /// The name of the block, if these are nested settings
+    parent: Option<&'de SettingName>,
+
+    /// The settings which are left
+    settings: std::vec::IntoIter<(&'de SettingName, ValueRef<'de>)>,
+
+    /// The value of the setting whose name was just handed out
+    value: Option<ValueDeserializer<'de>>,
+}
+
+impl<'de> Settings<'de> {
+    /// Hand out the given settings, which belong to the block named `parent` if there is one
+    fn new(
+        parent: Option<&'de SettingName>,
+        settings: Vec<(&'de SettingName, ValueRef<'de>)>,
+    ) -> Self {
+        Self {
+            parent,
+            settings: settings.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for Settings<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((setting_name, value)) = self.settings.next() else {
+            return Ok(None);
+        };
+
+        let name = match self.parent {
+            Some(parent) => format!("{parent}.{setting_name}"),
+            None => setting_name.to_string(),
+        };
+        self.value = Some(ValueDeserializer {
+            setting_name,
+            name,
+            value,
+        });
+
+        seed.deserialize(BorrowedStrDeserializer::new(setting_name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(serde::de::Error::custom("a value was read before its name")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.settings.len())
+    }
+}
+
+/// Reads a single value, converting it to whatever the [Visitor] asks for, or a block of nested settings
+pub(crate) struct ValueDeserializer<'de> {
+    /// The name of the setting
+    setting_name: &'de SettingName,
+
+    /// The name of the setting, prefixed with the name of its block if it is nested, used in errors
+    name: String,
+
+    /// The value of the setting
+    value: ValueRef<'de>,
+}
+
+impl ValueDeserializer<'_> {
+    /// Describe a value which can't be converted to what was asked for
+    fn invalid(&self, value: &str, expected: &str) -> Error {
+        Error::Serde(format!(
+            "invalid value '{value}' for '{}', expected {expected}",
+            self.name
+        ))
+    }
+}
+
+/// Implement the methods of [Deserializer] which parse a single value into a [std::str::FromStr] type
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident: $type:ty, $expected:literal;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.value {
+                    ValueRef::Single(value) => match value.trim().parse::<$type>() {
+                        Ok(parsed) => visitor.$visit(parsed),
+                        Err(_) => Err(self.invalid(value, $expected)),
+                    },
+                    ValueRef::Nested(_) => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            ValueRef::Single(value) => visitor.visit_borrowed_str(value),
+            ValueRef::Nested(nested) => {
+                let settings = nested
+                    .iter()
+                    .map(|nested| (nested.name(), ValueRef::Single(nested.value())))
+                    .collect();
+                visitor.visit_map(Settings::new(Some(self.setting_name), settings))
+            }
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            ValueRef::Single(value) if value.trim().eq_ignore_ascii_case("true") => {
+                visitor.visit_bool(true)
+            }
+            ValueRef::Single(value) if value.trim().eq_ignore_ascii_case("false") => {
+                visitor.visit_bool(false)
+            }
+            ValueRef::Single(value) => Err(self.invalid(value, "true or false")),
+            ValueRef::Nested(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8: i8, "an integer";
+        deserialize_i16 => visit_i16: i16, "an integer";
+        deserialize_i32 => visit_i32: i32, "an integer";
+        deserialize_i64 => visit_i64: i64, "an integer";
+        deserialize_i128 => visit_i128: i128, "an integer";
+        deserialize_u8 => visit_u8: u8, "a positive integer";
+        deserialize_u16 => visit_u16: u16, "a positive integer";
+        deserialize_u32 => visit_u32: u32, "a positive integer";
+        deserialize_u64 => visit_u64: u64, "a positive integer";
+        deserialize_u128 => visit_u128: u128, "a positive integer";
+        deserialize_f32 => visit_f32: f32, "a number";
+        deserialize_f64 => visit_f64: f64, "a number";
+        deserialize_char => visit_char: char, "a single character";
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A single value is read as a comma-separated list
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            ValueRef::Single(value) => {
+                let setting_name = self.setting_name;
+                let name = self.name;
+                let items = list_items(value).map(|item| ValueDeserializer {
+                    setting_name,
+                    name: name.clone(),
+                    value: ValueRef::Single(item),
+                });
+
+                let mut seq = SeqDeserializer::new(items);
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            ValueRef::Nested(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// A single value is read as the name of a unit variant
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            ValueRef::Single(value) => BorrowedStrDeserializer::<Error>::new(value.trim())
+                .deserialize_enum(name, variants, visitor)
+                .map_err(|_| self.invalid(value, &format!("one of {}", variants.join(", ")))),
+            ValueRef::Nested(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// A value produced by the [ValueSerializer]
+#[derive(Debug, Clone)]
+pub(crate) enum Serialized {
+    /// A single value, and how to read an existing value the same way
+    Single(String, Reread),
+
+    /// A value which is absent, such as [None], which removes the setting
+    Absent,
+
+    /// A struct or a map, which is a block of nested settings, or the whole section at the top level
+    Block(Vec<(String, Serialized)>),
+}
+
+/// Reads a value of a given type through a [ValueDeserializer] and serializes it again
+type Read = fn(ValueDeserializer<'_>) -> Result<Serialized, Error>;
+
+/// How to read an existing value as the type a new value was serialized from, so that the two can be compared
+/// by what they mean rather than by how they are written, such as `True` and `true`, or `1.50` and `1.5`
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Reread {
+    /// A single value
+    Value(Read),
+
+    /// A comma-separated list, each item of which is read in the given way
+    List(Read),
+
+    /// A value which can only be compared as it is written, such as a list of lists
+    Text,
+}
+
+impl Reread {
+    /// Whether the existing value of the setting with the given name means the same as the new one
+    fn matches(self, setting_name: &SettingName, existing: &str, new: &str) -> bool {
+        if existing == new {
+            return true;
+        }
+
+        let value = ValueDeserializer {
+            setting_name,
+            name: setting_name.to_string(),
+            value: ValueRef::Single(existing),
+        };
+
+        let reread = match self {
+            Reread::Value(read) => read(value),
+            Reread::List(read) => reread_list(value, read),
+            Reread::Text => return false,
+        };
+
+        matches!(reread, Ok(Serialized::Single(reread, _)) if reread == new)
+    }
+}
+
+/// Read a value as the type `T` and serialize it again
+fn reread<T: DeserializeOwned + Serialize>(
+    value: ValueDeserializer<'_>,
+) -> Result<Serialized, Error> {
+    T::deserialize(value)?.serialize(ValueSerializer)
+}
+
+/// Read a value as the name of a unit variant, since the type of the enum isn't known
+fn reread_variant(value: ValueDeserializer<'_>) -> Result<Serialized, Error> {
+    match value.value {
+        ValueRef::Single(variant) => Ok(Serialized::Single(
+            variant.trim().to_string(),
+            Reread::Value(reread_variant),
+        )),
+        ValueRef::Nested(_) => Err(unsupported("nested settings")),
+    }
+}
+
+/// Read a value as a comma-separated list, reading each item in the given way, and serialize it again
+fn reread_list(value: ValueDeserializer<'_>, read: Read) -> Result<Serialized, Error> {
+    let ValueRef::Single(items) = value.value else {
+        return Err(unsupported("nested settings"));
+    };
+
+    let mut list = ListSerializer::default();
+    for item in list_items(items) {
+        list.push(read(ValueDeserializer {
+            setting_name: value.setting_name,
+            name: value.name.clone(),
+            value: ValueRef::Single(item),
+        })?)?;
+    }
+
+    SerializeSeq::end(list)
+}
+
+/// The items of a value which is read as a comma-separated list
+fn list_items(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// Turns a value into a [Serialized], writing scalars, unit variants and lists in the form that
+/// [SectionDeserializer] reads them back in
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ValueSerializer;
+
+/// Implement the methods of [Serializer] which write a value through its [Display] implementation
+macro_rules! serialize_displayed {
+    ($($method:ident: $type:ty),*) => {
+        $(
+            fn $method(self, value: $type) -> Result<Serialized, Error> {
+                Ok(Serialized::Single(value.to_string(), Reread::Value(reread::<$type>)))
+            }
+        )*
+    };
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = Impossible<Serialized, Error>;
+    type SerializeMap = BlockSerializer;
+    type SerializeStruct = BlockSerializer;
+    type SerializeStructVariant = Impossible<Serialized, Error>;
+
+    serialize_displayed!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char
+    );
+
+    fn serialize_str(self, value: &str) -> Result<Serialized, Error> {
+        Ok(Serialized::Single(
+            value.to_string(),
+            Reread::Value(reread::<String>),
+        ))
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Serialized, Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Serialized, Error> {
+        Ok(Serialized::Absent)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Serialized, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Serialized, Error> {
+        Ok(Serialized::Absent)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Serialized, Error> {
+        Ok(Serialized::Absent)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Serialized, Error> {
+        Ok(Serialized::Single(
+            variant.to_string(),
+            Reread::Value(reread_variant),
+        ))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Serialized, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Serialized, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ListSerializer, Error> {
+        Ok(ListSerializer::default())
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<ListSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<BlockSerializer, Error> {
+        Ok(BlockSerializer::default())
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<BlockSerializer, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+/// Describe a kind of value which can't be written to a config file
+fn unsupported(kind: &str) -> Error {
+    Error::Serde(format!("{kind} can't be written to a config file"))
+}
+
+/// Collects the items of a list, which are written as a single comma-separated value. An empty list is written
+/// as [Serialized::Absent], since a setting can't have an empty value.
+#[derive(Debug, Default)]
+pub(crate) struct ListSerializer {
+    /// The items so far
+    items: Vec<String>,
+
+    /// How to read an existing item the same way as the first item
+    reread: Option<Reread>,
+}
+
+impl ListSerializer {
+    /// Add a serialized item to the list
+    fn push(&mut self, item: Serialized) -> Result<(), Error> {
+        match item {
+            Serialized::Single(item, reread) => {
+                self.items.push(item);
+                self.reread.get_or_insert(reread);
+            }
+            Serialized::Absent => (),
+            Serialized::Block(_) => return Err(unsupported("lists of structs or maps")),
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSeq for ListSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value.serialize(ValueSerializer)?)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        let reread = match self.reread {
+            Some(Reread::Value(read)) => Reread::List(read),
+            _ => Reread::Text,
+        };
+
+        match self.items.is_empty() {
+            true => Ok(Serialized::Absent),
+            false => Ok(Serialized::Single(self.items.join(","), reread)),
+        }
+    }
+}
+
+impl SerializeTuple for ListSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for ListSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+/// Collects the entries of a struct or a map
+#[derive(Debug, Default)]
+pub(crate) struct BlockSerializer {
+    /// The entries so far
+    entries: Vec<(String, Serialized)>,
+
+    /// The key of the entry whose value comes next
+    key: Option<String>,
+}
+
+impl SerializeMap for BlockSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        match key.serialize(ValueSerializer)? {
+            Serialized::Single(key, _) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(unsupported("map keys which aren't strings")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Serde("a value was written before its key".to_string()))?;
+        self.entries.push((key, value.serialize(ValueSerializer)?));
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        Ok(Serialized::Block(self.entries))
+    }
+}
+
+impl SerializeStruct for BlockSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.entries
+            .push((key.to_string(), value.serialize(ValueSerializer)?));
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        Ok(Serialized::Block(self.entries))
+    }
+}
+
+/// A change to a single setting, as asked for by [Section::update_from]
+#[derive(Debug)]
+enum Update {
+    /// Remove the setting
+    Remove,
+
+    /// Give the setting a single value, unless its value already means the same when read as described by the
+    /// [Reread]
+    Set(Value, Reread),
+
+    /// Give the setting a block of nested settings, removing those which are [None]
+    SetNested(Vec<(SettingName, Option<(Value, Reread)>)>),
+}
+
+impl Update {
+    /// Turn a serialized value into an update of the setting with the given name, checking that it fits in a
+    /// config file
+    fn new(setting_name: &SettingName, value: Serialized) -> Result<Self, Error> {
+        let nested = match value {
+            Serialized::Single(value, reread) => {
+                return Ok(Update::Set(checked_value(setting_name, &value)?, reread))
+            }
+            Serialized::Absent => return Ok(Update::Remove),
+            Serialized::Block(nested) => nested,
+        };
+
+        let nested = nested
+            .into_iter()
+            .map(|(nested_name, nested_value)| {
+                let nested_value = match nested_value {
+                    Serialized::Single(value, reread) => Some((
+                        checked_value(&format!("{setting_name}.{nested_name}"), &value)?,
+                        reread,
+                    )),
+                    Serialized::Absent => None,
+                    Serialized::Block(_) => {
+                        return Err(Error::Serde(format!(
+                            "'{setting_name}.{nested_name}' is nested too deeply for a config file"
+                        )))
+                    }
+                };
+                Ok((nested_name.parse::<SettingName>()?, nested_value))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Update::SetNested(nested))
+    }
+
+    /// Apply the update to the setting with the given name, leaving it alone if it already matches
+    fn apply<T: Header>(self, section: &mut Section<T>, setting_name: SettingName) {
+        match self {
+            Update::Remove => {
+                section.remove_setting(&setting_name);
+            }
+            Update::Set(value, reread) => {
+                let existing = section.get_value(&setting_name);
+                let unchanged = matches!(existing, Some(ValueType::Single(existing))
+                    if reread.matches(&setting_name, existing, &value));
+
+                if !unchanged {
+                    section.set(setting_name, value);
+                }
+            }
+            Update::SetNested(nested_settings) => {
+                for (nested_name, nested_value) in nested_settings {
+                    let existing = section
+                        .get_nested_setting(&setting_name, &nested_name)
+                        .map(|nested| nested.value());
+
+                    match nested_value {
+                        Some((value, reread))
+                            if existing.is_some_and(|existing| {
+                                reread.matches(&nested_name, existing, &value)
+                            }) => {}
+                        Some((value, _)) => {
+                            section.convert_to_nested(&setting_name);
+
+                            #[allow(clippy::unwrap_used)]
+                            // This cannot fail because the setting is either missing or was converted to a block
+                            section
+                                .set_nested(setting_name.clone(), nested_name, value)
+                                .unwrap();
+                        }
+                        None => {
+                            section.remove_nested_setting(&setting_name, &nested_name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Read a serialized string as a [Value], failing if it couldn't be read back from the file as the same value,
+/// such as a string which is empty, spans several lines or holds a comment
+fn checked_value(setting_name: &str, value: &str) -> Result<Value, Error> {
+    value.parse().map_err(|_| {
+        Error::Serde(format!(
+            "invalid value '{}' for '{setting_name}', it can't be written to a config file",
+            value.escape_debug()
+        ))
+    })
+}
+
+/// Change the settings of the section to match the serialized value, leaving those which already match alone,
+/// as described in [Section::update_from]
+pub(crate) fn update_section<T: Header, S: ?Sized + Serialize>(
+    section: &mut Section<T>,
+    value: &S,
+) -> Result<(), Error> {
+    let Serialized::Block(entries) = value.serialize(ValueSerializer)? else {
+        return Err(Error::Serde(
+            "only a struct or a map can be written to a section".to_string(),
+        ));
+    };
+
+    // Everything is checked first, so that the section is left alone if anything is wrong
+    let updates = entries
+        .into_iter()
+        .map(|(setting_name, value)| {
+            let setting_name = setting_name.parse::<SettingName>()?;
+            let update = Update::new(&setting_name, value)?;
+            Ok((setting_name, update))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    for (setting_name, update) in updates {
+        update.apply(section, setting_name);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Contains the [serde] implementations of the types which have a string form, such as paths, names and values.
+//! They are written and read as that string, so a [crate::SettingPath] is written as `"profile.A.region"`.
+
+use super::{
+    ConfigPath, NestedSettingPath, SectionName, SectionPath, SectionType, SettingName, SettingPath,
+    Value,
+};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Implement [Serialize] through [std::fmt::Display] and [Deserialize] through [TryFrom] or
+/// [std::str::FromStr], depending on how the type is read from a string, for each given type
+macro_rules! impl_string_serde {
+    (try_from: $($type:ty),*) => {
+        $(
+            impl_string_serde!(@serialize $type);
+
+            impl<'de> Deserialize<'de> for $type {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = String::deserialize(deserializer)?;
+
+                    Self::try_from(value.as_str()).map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+    (from_str: $($type:ty),*) => {
+        $(
+            impl_string_serde!(@serialize $type);
+
+            impl<'de> Deserialize<'de> for $type {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = String::deserialize(deserializer)?;
+
+                    value.parse().map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+    (@serialize $type:ty) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+    };
+}
+
+impl_string_serde!(
+    try_from: ConfigPath,
+    SectionPath,
+    SettingPath,
+    NestedSettingPath
+);
+
+impl_string_serde!(from_str: SectionType, SectionName, SettingName, Value);
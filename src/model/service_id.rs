@@ -0,0 +1,76 @@
+//! Contains the [ServiceId] type, which names a service in a `[services]` section.
+
+use super::SettingName;
+use crate::Error;
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+/// The identifier of an AWS service, as used for its block of settings in a `[services]` section, such as
+/// `dynamodb` or `elastic_beanstalk`. Like the SDKs, parsing one lowercases it and replaces spaces with
+/// underscores, so `Elastic Beanstalk` becomes `elastic_beanstalk`. What remains has to be a valid
+/// [SettingName].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServiceId(String);
+
+impl ServiceId {
+    /// The identifier of the service whose block of settings has the given name, which is already a valid
+    /// [SettingName] but may not be lowercase
+    pub(crate) fn of(setting_name: &SettingName) -> Self {
+        Self(setting_name.to_lowercase())
+    }
+}
+
+impl FromStr for ServiceId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace(' ', "_");
+
+        match normalized.parse::<SettingName>() {
+            Ok(setting_name) => Ok(Self::of(&setting_name)),
+            Err(_) => Err(Error::InvalidValue {
+                value: s.to_string(),
+                expected: "a service identifier such as 'dynamodb' or 'Elastic Beanstalk'",
+            }),
+        }
+    }
+}
+
+impl Display for ServiceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for ServiceId {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ServiceId;
+
+    #[test]
+    fn service_ids_are_normalized_like_the_sdks() {
+        for (input, expected) in [
+            ("dynamodb", "dynamodb"),
+            ("DynamoDB", "dynamodb"),
+            ("Elastic Beanstalk", "elastic_beanstalk"),
+            ("  S3 Control ", "s3_control"),
+        ] {
+            let service_id = input.parse::<ServiceId>().expect("Should be valid");
+
+            assert_eq!(service_id.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn service_ids_which_cannot_be_setting_names_are_rejected() {
+        for input in ["", "   ", "s3-control", "ec2.endpoint_url"] {
+            assert!(input.parse::<ServiceId>().is_err(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Contains the [ServiceSettings] type, a typed view of the settings of one service in a `[services]` section.
+
+use super::{nested_settings::NestedSettings, EndpointUrl, NestedSetting, ServiceId, Value};
+use crate::Error;
+
+/// The block of settings of one service in a `[services]` section, as returned by
+/// [crate::ServicesSection::service]. Given the section below, the settings of `dynamodb` are its
+/// `endpoint_url` and `max_attempts`:
+///
+/// ```ini
+/// [services local]
+/// dynamodb =
+///   endpoint_url = http://localhost:8000
+///   max_attempts = 1
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServiceSettings<'a> {
+    /// The service these settings apply to
+    service_id: ServiceId,
+
+    /// The nested settings of the service
+    settings: &'a NestedSettings,
+}
+
+impl<'a> ServiceSettings<'a> {
+    /// The name of the setting for the endpoint of the service
+    const ENDPOINT_URL: &'static str = "endpoint_url";
+
+    /// Create a view of the nested settings of the given service
+    pub(crate) fn new(service_id: ServiceId, settings: &'a NestedSettings) -> Self {
+        Self {
+            service_id,
+            settings,
+        }
+    }
+
+    /// The service these settings apply to
+    pub fn service_id(&self) -> &ServiceId {
+        &self.service_id
+    }
+
+    /// The endpoint used for the service, from its `endpoint_url` setting
+    pub fn endpoint_url(&self) -> Result<Option<EndpointUrl>, Error> {
+        self.get(Self::ENDPOINT_URL)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|error| Error::invalid_setting(Self::ENDPOINT_URL, error))
+            })
+            .transpose()
+    }
+
+    /// The [Value] of any other setting of the service, such as `max_attempts`
+    pub fn get(&self, setting_name: &str) -> Option<&'a Value> {
+        self.settings
+            .iter()
+            .find(|nested| *nested.name() == *setting_name)
+            .map(|nested| nested.value())
+    }
+
+    /// Iterate over all the settings of the service, in the order they appear
+    pub fn settings(&self) -> impl Iterator<Item = &'a NestedSetting> {
+        self.settings.iter()
+    }
+}
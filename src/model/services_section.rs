@@ -0,0 +1,148 @@
+//! Contains the [ServicesSection] type, a strongly typed view of a `[services]` [Section] of an
+//! [crate::AwsConfigFile].
+
+use super::{
+    header::ConfigHeader, EndpointUrl, Section, SectionName, SectionType, ServiceId,
+    ServiceSettings, Setting, SettingName, Value, ValueType,
+};
+use crate::Error;
+use std::ops::{Deref, DerefMut};
+
+/// A strongly typed view of a `[services]` [Section], which maps each [ServiceId] to the [ServiceSettings] of
+/// that service, such as its `endpoint_url`. Profiles use the section through their `services` setting. It is
+/// created by [crate::AwsConfigFile::services_section], [crate::AwsConfigFile::services_section_mut] and
+/// [crate::AwsConfigFile::insert_services_section], or by [ServicesSection::new] from any `[services]` section.
+///
+/// Services are found by their normalized [ServiceId], so a block written as `DynamoDB =` is found as
+/// `dynamodb`, and is changed under the name it already has.
+///
+/// ```
+/// # use aws_config_mod::AwsConfigFile;
+/// let mut config: AwsConfigFile = "[services local]\ndynamodb =\n  endpoint_url = http://localhost:8000\n"
+///     .parse()
+///     .unwrap();
+///
+/// let mut services = config.services_section_mut(&"local".parse().unwrap()).unwrap();
+/// services
+///     .set_endpoint(&"S3".parse().unwrap(), "http://localhost:4566".parse().unwrap())
+///     .unwrap();
+///
+/// assert_eq!(
+///     config.to_string(),
+///     "[services local]\ndynamodb =\n  endpoint_url = http://localhost:8000\ns3 =\n  endpoint_url = http://localhost:4566\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct ServicesSection<S> {
+    /// The `[services]` section this is a view of
+    pub(crate) section: S,
+}
+
+impl<S> ServicesSection<S>
+where
+    S: Deref<Target = Section<ConfigHeader>>,
+{
+    /// The name of the nested setting for the endpoint of a service
+    const ENDPOINT_URL: &'static str = "endpoint_url";
+
+    /// Create a view of the given section, or return [None] if it isn't a `[services]` section
+    pub fn new(section: S) -> Option<Self> {
+        (*section.get_type() == SectionType::Services).then_some(Self { section })
+    }
+
+    /// The `[services]` section this is a view of
+    pub fn section(&self) -> &Section<ConfigHeader> {
+        &self.section
+    }
+
+    /// The name of the section, which profiles refer to through their `services` setting
+    pub fn name(&self) -> Option<&SectionName> {
+        self.section.get_name()
+    }
+
+    /// Iterate over the services which have a block of settings, in the order they appear. Settings of the
+    /// section which hold a single value rather than a block are left out.
+    pub fn services(&self) -> impl Iterator<Item = ServiceSettings<'_>> {
+        self.section
+            .settings()
+            .iter()
+            .filter_map(|setting| match setting.value() {
+                ValueType::Nested(nested) => {
+                    Some(ServiceSettings::new(ServiceId::of(setting.name()), nested))
+                }
+                ValueType::Single(_) => None,
+            })
+    }
+
+    /// The settings of the given service, if it has a block of settings
+    pub fn service(&self, service_id: &ServiceId) -> Option<ServiceSettings<'_>> {
+        self.services()
+            .find(|service| service.service_id() == service_id)
+    }
+
+    /// The name under which the given service is written in the section, which is its [ServiceId] unless the
+    /// section already spells it differently, such as `DynamoDB`
+    fn setting_name_of(&self, service_id: &ServiceId) -> SettingName {
+        self.section
+            .settings()
+            .iter()
+            .map(Setting::name)
+            .find(|setting_name| ServiceId::of(setting_name) == *service_id)
+            .cloned()
+            .unwrap_or_else(|| SettingName(service_id.to_string()))
+    }
+}
+
+impl<S> ServicesSection<S>
+where
+    S: DerefMut<Target = Section<ConfigHeader>>,
+{
+    /// The `[services]` section this is a view of, which can be changed directly
+    pub fn section_mut(&mut self) -> &mut Section<ConfigHeader> {
+        &mut self.section
+    }
+
+    /// Set the `endpoint_url` of the given service, adding a block of settings for the service if it doesn't have
+    /// one. Fails with [Error::NotNested] if the service is set to a single value instead of a block.
+    pub fn set_endpoint(
+        &mut self,
+        service_id: &ServiceId,
+        endpoint_url: EndpointUrl,
+    ) -> Result<(), Error> {
+        self.set(
+            service_id,
+            SettingName(Self::ENDPOINT_URL.to_string()),
+            Value::from(&*endpoint_url),
+        )
+    }
+
+    /// Set any setting of the given service, adding a block of settings for the service if it doesn't have one.
+    /// Fails with [Error::NotNested] if the service is set to a single value instead of a block.
+    pub fn set(
+        &mut self,
+        service_id: &ServiceId,
+        setting_name: SettingName,
+        value: Value,
+    ) -> Result<(), Error> {
+        let service_name = self.setting_name_of(service_id);
+
+        self.section.set_nested(service_name, setting_name, value)
+    }
+
+    /// Remove a setting of the given service, returning its [Value] if it existed. The block of the service is
+    /// removed along with its last setting.
+    pub fn remove(&mut self, service_id: &ServiceId, setting_name: &SettingName) -> Option<Value> {
+        let service_name = self.setting_name_of(service_id);
+
+        self.section
+            .remove_nested_setting(&service_name, setting_name)
+            .map(|nested| nested.value().clone())
+    }
+
+    /// Remove the given service along with all of its settings, returning it if it existed
+    pub fn remove_service(&mut self, service_id: &ServiceId) -> Option<Setting> {
+        let service_name = self.setting_name_of(service_id);
+
+        self.section.remove_setting(&service_name)
+    }
+}
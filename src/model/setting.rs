@@ -2,13 +2,16 @@
 //! headers and are comprised of a settign name followed by and equals sign, which is then followed by
 //! one of the following:
 //!
-//! - a value, which is a sequence of non-whitespace characters excluding the # character
+//! - a value, which is the rest of the line up to an optional comment, excluding surrounding whitespace
 //! - a newline followed by a list of indented settings
 
 use super::{
-    equal::Equal, setting_name::SettingName, value_type::ValueType, whitespace::Whitespace,
+    equal::Equal, format_options::FormatOptions, nested_settings::NestedSettings,
+    setting_name::SettingName, style::Style, value_type::ValueType, whitespace::Whitespace,
+    LineEnding, Value,
 };
 use crate::lexer::{Parsable, ParserOutput};
+use nom::{character::complete::space0, combinator::recognize, sequence::pair};
 use std::{
     fmt::{Debug, Display},
     hash::Hash,
@@ -17,7 +20,7 @@ use std::{
 /// Represents a setting in its entirety, including indentation, its name and value, and a comment
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Setting {
-    /// The whitespace and comments on the preceding line
+    /// Blank lines and comment lines between the previous line and this setting, followed by its indentation
     pub(crate) leading_whitespace: Whitespace,
 
     /// The name of the [Setting]
@@ -30,16 +33,34 @@ pub struct Setting {
     /// The '=' sign appearing between the value and the name. This is to track what the original formatting
     /// of the setting was before it was parsed so that it can be returned to its original state, even after editing.
     pub(crate) equal: Equal,
+
+    /// Any whitespace or comment following a single value on the same line, including the line ending.
+    /// For nested settings this is held by the [crate::NestedSettings] instead.
+    pub(crate) trailing_whitespace: Whitespace,
 }
 
 impl Setting {
     /// Create a new [Setting]
     pub fn new(setting_name: SettingName, value: ValueType) -> Self {
+        Self::with_style(setting_name, value, &Style::default())
+    }
+
+    /// Create a new [Setting] which is formatted in the given [Style]
+    pub(crate) fn with_style(setting_name: SettingName, value: ValueType, style: &Style) -> Self {
+        let (equal, trailing_whitespace) = match value {
+            ValueType::Single(_) => (style.equal.clone(), Whitespace::newline(style.line_ending)),
+            ValueType::Nested(_) => (
+                style.equal.without_trailing_padding(),
+                Whitespace::default(),
+            ),
+        };
+
         Self {
-            leading_whitespace: Whitespace::newline(),
+            leading_whitespace: Whitespace::default(),
             setting_name,
             value,
-            equal: Equal::default(),
+            equal,
+            trailing_whitespace,
         }
     }
 
@@ -52,14 +73,137 @@ impl Setting {
     pub fn value(&self) -> &ValueType {
         &self.value
     }
+
+    /// Change the [Value] of this setting
+    pub fn set(&mut self, value: Value) {
+        self.value = ValueType::Single(value);
+    }
+
+    /// Turn a setting with a single value into an empty block of nested settings, keeping any comment on its
+    /// line. Returns the value that was replaced, or [None] if the setting was already nested.
+    pub(crate) fn convert_to_nested(&mut self, line_ending: LineEnding) -> Option<Value> {
+        let ValueType::Single(_) = self.value else {
+            return None;
+        };
+
+        let mut nested = NestedSettings::new(line_ending);
+        nested.leading_whitespace = std::mem::take(&mut self.trailing_whitespace);
+        self.equal = self.equal.without_trailing_padding();
+
+        match std::mem::replace(&mut self.value, ValueType::Nested(nested)) {
+            ValueType::Single(value) => Some(value),
+            ValueType::Nested(_) => None,
+        }
+    }
+
+    /// The whitespace holding the line ending of the last line of this setting, which is the line of the
+    /// last nested setting if there are any.
+    pub(crate) fn line_end_mut(&mut self) -> &mut Whitespace {
+        match &mut self.value {
+            ValueType::Single(_) => &mut self.trailing_whitespace,
+            ValueType::Nested(nested) => nested.line_end_mut(),
+        }
+    }
+
+    /// The comment lines directly above the setting, without the '#' which starts each of them
+    pub fn leading_comments(&self) -> impl Iterator<Item = &str> {
+        self.leading_whitespace.comments()
+    }
+
+    /// Replace the comment lines above the setting. Each comment is written on its own line with the same
+    /// indentation as the setting, and a comment which spans several lines becomes several comment lines.
+    /// Each line ends with the given [LineEnding], which should be [crate::Section::line_ending] of the section the
+    /// setting is in.
+    pub fn set_leading_comments(
+        &mut self,
+        comments: impl IntoIterator<Item = impl AsRef<str>>,
+        line_ending: LineEnding,
+    ) {
+        let indent = self.leading_whitespace.indentation().to_string();
+
+        self.leading_whitespace
+            .set_comments(comments, &indent, line_ending)
+    }
+
+    /// The number of blank lines which separate this setting from the content above it
+    pub fn blank_lines_before(&self) -> usize {
+        self.leading_whitespace.blank_lines()
+    }
+
+    /// Change the number of blank lines which separate this setting from the content above it. Each blank line
+    /// ends with the given [LineEnding], which should be [crate::Section::line_ending] of the section the setting is in.
+    pub fn set_blank_lines_before(&mut self, count: usize, line_ending: LineEnding) {
+        self.leading_whitespace.set_blank_lines(count, line_ending)
+    }
+
+    /// The text of the comment after the value on the same line, without the '#' which starts it. For a
+    /// setting with nested settings, this is the comment after the '='.
+    pub fn inline_comment(&self) -> Option<&str> {
+        match &self.value {
+            ValueType::Single(_) => self.trailing_whitespace.inline_comment(),
+            ValueType::Nested(nested) => nested.leading_whitespace.inline_comment(),
+        }
+    }
+
+    /// Replace the comment after the value on the same line, or remove it by passing [None]
+    pub fn set_inline_comment(&mut self, comment: Option<&str>) {
+        match &mut self.value {
+            ValueType::Single(_) => self.trailing_whitespace.set_inline_comment(comment),
+            ValueType::Nested(nested) => {
+                // Move the spaces after the '=' in front of the comment, where they are kept even without one
+                let padding = &self.equal[self.equal.trim_end().len()..];
+                if nested.leading_whitespace.inline_comment().is_none() && !padding.is_empty() {
+                    nested.leading_whitespace.0.insert_str(0, padding);
+                }
+
+                self.equal = self.equal.without_trailing_padding();
+                nested.leading_whitespace.set_inline_comment(comment)
+            }
+        }
+    }
+
+    /// Rewrite the spacing, indentation and line endings of this setting and its nested settings as described
+    /// by the [FormatOptions]
+    pub(crate) fn format(&mut self, options: &FormatOptions, line_ending: LineEnding) {
+        self.leading_whitespace.format_lines(None, "", line_ending);
+
+        match &mut self.value {
+            ValueType::Single(_) => {
+                self.equal = options.equal();
+                self.trailing_whitespace.end_line(line_ending);
+                self.trailing_whitespace
+                    .format_line_end(options.comment_spacing(), line_ending);
+            }
+            ValueType::Nested(nested) => {
+                self.equal = options.equal().without_trailing_padding();
+                nested.format(options, line_ending);
+            }
+        }
+    }
+
+    /// Lines among the nested settings of this setting which could not be parsed in lenient mode
+    pub(crate) fn raw_lines(&self) -> impl Iterator<Item = &str> {
+        let nested = match &self.value {
+            ValueType::Single(_) => None,
+            ValueType::Nested(nested) => Some(nested),
+        };
+
+        nested
+            .into_iter()
+            .flat_map(|nested| nested.raw_lines.lines())
+    }
 }
 
 impl Display for Setting {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}{}{}{}",
-            self.leading_whitespace, self.setting_name, self.equal, self.value,
+            "{}{}{}{}{}",
+            self.leading_whitespace,
+            self.setting_name,
+            self.equal,
+            self.value,
+            self.trailing_whitespace,
         )
     }
 }
@@ -68,16 +212,21 @@ impl<'a> Parsable<'a> for Setting {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (next, leading_whitespace) = Whitespace::parse(input)?;
+        let (next, leading_whitespace) = recognize(pair(Whitespace::parse_lines, space0))(input)?;
         let (next, setting_name) = SettingName::parse(next)?;
         let (next, equal) = Equal::parse(next)?;
         let (next, value) = ValueType::parse(next)?;
+        let (next, trailing_whitespace) = match value {
+            ValueType::Single(_) => Whitespace::parse_line_end(next)?,
+            ValueType::Nested(_) => (next, Whitespace::default()),
+        };
 
         let setting = Self {
             setting_name,
             value,
             equal,
-            leading_whitespace,
+            leading_whitespace: Whitespace(leading_whitespace.to_string()),
+            trailing_whitespace,
         };
 
         Ok((next, setting))
@@ -108,6 +257,24 @@ region=us-west-2"#;
         assert_eq!(set.to_string(), setting)
     }
 
+    #[test]
+    fn parses_a_setting_with_spaces_in_the_value() {
+        let setting = r#"
+credential_process = /usr/local/bin/helper --profile x   # helper"#;
+
+        let (rest, set) = Setting::parse(setting).expect("Should be valid");
+
+        assert!(rest.is_empty());
+        assert_eq!("credential_process", set.name());
+
+        match set.value() {
+            crate::ValueType::Single(value) => {
+                assert_eq!(value, "/usr/local/bin/helper --profile x")
+            }
+            crate::ValueType::Nested(_) => panic!("Should not be nested"),
+        }
+    }
+
     #[test]
     fn parses_a_nested_setting() {
         let setting = r#"ec2 = 
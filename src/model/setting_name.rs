@@ -1,16 +1,17 @@
 //! Contains items related to parsing and stringifying setting names. Setting names are the
 //! identifiers for the setting values in a configuration file.
 
-use crate::lexer::{to_owned_input, Parsable, ParserOutput};
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
 use nom::{
     branch::alt, bytes::complete::tag, character::complete::alphanumeric1, combinator::recognize,
-    multi::many0_count,
+    error::context, multi::many1_count,
 };
 use std::{fmt::Display, ops::Deref, str::FromStr};
 
 /// Represents the name of a setting; in other words, the part that comes before the '=' sign.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
-pub struct SettingName(String);
+pub struct SettingName(pub(crate) String);
 
 impl PartialEq<str> for SettingName {
     fn eq(&self, other: &str) -> bool {
@@ -42,10 +43,7 @@ impl FromStr for SettingName {
     type Err = crate::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::parse(s)
-            .map(|a| a.1)
-            .map_err(to_owned_input)
-            .map_err(crate::Error::from)
+        Ok(parse_complete::<Self>(s)?)
     }
 }
 
@@ -53,7 +51,10 @@ impl<'a> Parsable<'a> for SettingName {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (input, setting_name) = recognize(many0_count(alt((alphanumeric1, tag("_")))))(input)?;
+        let (input, setting_name) = context(
+            Expected::SettingName.label(),
+            recognize(many1_count(alt((alphanumeric1, tag("_"))))),
+        )(input)?;
         Ok((input, Self(setting_name.to_string())))
     }
 }
@@ -78,4 +79,12 @@ mod test {
             assert_eq!(&value.to_string(), name)
         }
     }
+
+    #[test]
+    fn rejects_invalid_setting_names() {
+        for name in ["", "region=", "my region"] {
+            name.parse::<SettingName>()
+                .expect_err("Should not be a valid setting name");
+        }
+    }
 }
@@ -1,9 +1,11 @@
 //! Contains items related to parsing setting paths. Setting paths are comprise of a section type, a section name, and a setting name.
 //! They are used to identify a specific setting from the top level of a configuration file.
 
-use super::{SectionPath, SettingName};
-use crate::lexer::{to_owned_input, Parsable};
-use nom::{bytes::complete::tag, combinator::eof};
+use super::{section_path::unnamed_section_path, SectionPath, SettingName};
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
+use nom::{bytes::complete::tag, error::context};
+use std::fmt::Display;
 
 /// A path to a [crate::Setting]. The path includes the [crate::SectionType], the [crate::SectionName],
 /// and the [SettingName] of the setting to be accessed.
@@ -19,9 +21,7 @@ impl TryFrom<&str> for SettingPath {
     type Error = crate::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let (_, config_path) = Self::parse(value).map_err(to_owned_input)?;
-
-        Ok(config_path.to_owned())
+        Ok(parse_complete::<Self>(value)?)
     }
 }
 
@@ -32,7 +32,7 @@ impl TryFrom<(&str, &str, &str)> for SettingPath {
         (section_type, section_name, setting_name): (&str, &str, &str),
     ) -> Result<Self, Self::Error> {
         let section_path = SectionPath::try_from((section_type, section_name))?;
-        let (_, setting_name) = SettingName::parse(setting_name).map_err(to_owned_input)?;
+        let setting_name = parse_complete::<SettingName>(setting_name)?;
 
         Ok(Self {
             section_path,
@@ -41,14 +41,32 @@ impl TryFrom<(&str, &str, &str)> for SettingPath {
     }
 }
 
+impl Display for SettingPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.section_path, self.setting_name)
+    }
+}
+
 impl<'a> Parsable<'a> for SettingPath {
     type Output = Self;
 
     fn parse(input: &'a str) -> crate::lexer::ParserOutput<'a, Self::Output> {
-        let (next, section_path) = SectionPath::parse(input)?;
-        let (next, _) = tag(".")(next)?;
+        Self::parse_in(SectionPath::parse, input)
+            .or_else(|error| Self::parse_in(unnamed_section_path, input).map_err(|_| error))
+    }
+}
+
+impl SettingPath {
+    /// Parse a [SettingPath] whose [SectionPath] is parsed by `section_path`. [Parsable::parse] reads a path
+    /// into a section which usually has no name, such as `preview.cloudfront`, with a name first, and without
+    /// one if that fails.
+    fn parse_in<'a>(
+        section_path: fn(&'a str) -> ParserOutput<'a, SectionPath>,
+        input: &'a str,
+    ) -> ParserOutput<'a, Self> {
+        let (next, section_path) = section_path(input)?;
+        let (next, _) = context(Expected::PathSeparator.label(), tag("."))(next)?;
         let (next, setting_name) = SettingName::parse(next)?;
-        let (next, _) = eof(next)?;
 
         let config_path = Self {
             section_path,
@@ -77,9 +95,17 @@ impl TryFrom<&str> for NestedSettingPath {
     type Error = crate::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let (_, nested_path) = Self::parse(value).map_err(to_owned_input)?;
+        Ok(parse_complete::<Self>(value)?)
+    }
+}
 
-        Ok(nested_path)
+impl Display for NestedSettingPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.section_path, self.setting_name, self.nested_setting_name
+        )
     }
 }
 
@@ -87,9 +113,21 @@ impl<'a> Parsable<'a> for NestedSettingPath {
     type Output = Self;
 
     fn parse(input: &'a str) -> crate::lexer::ParserOutput<'a, Self::Output> {
-        let (next, setting_path) = SettingPath::parse(input)?;
+        Self::parse_in(SectionPath::parse, input)
+            .or_else(|error| Self::parse_in(unnamed_section_path, input).map_err(|_| error))
+    }
+}
+
+impl NestedSettingPath {
+    /// Parse a [NestedSettingPath] whose [SectionPath] is parsed by `section_path`, in the same way as
+    /// [SettingPath::parse_in]
+    fn parse_in<'a>(
+        section_path: fn(&'a str) -> ParserOutput<'a, SectionPath>,
+        input: &'a str,
+    ) -> ParserOutput<'a, Self> {
+        let (next, setting_path) = SettingPath::parse_in(section_path, input)?;
+        let (next, _) = context(Expected::PathSeparator.label(), tag("."))(next)?;
         let (next, nested_setting_name) = SettingName::parse(next)?;
-        eof(next)?;
 
         let nested_path = Self {
             section_path: setting_path.section_path,
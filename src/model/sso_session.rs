@@ -0,0 +1,190 @@
+//! Contains the [SsoSession] type, a strongly typed view of an `[sso-session]` [Section] of an
+//! [crate::AwsConfigFile].
+
+use super::{header::ConfigHeader, Region, Section, SectionName, SectionType, SettingName, Value};
+use crate::Error;
+use std::ops::{Deref, DerefMut};
+
+/// A strongly typed view of an `[sso-session]` [Section], which holds the settings that profiles share to sign in
+/// through IAM Identity Center. It is created by [crate::AwsConfigFile::sso_session],
+/// [crate::AwsConfigFile::sso_session_mut] and [crate::AwsConfigFile::insert_sso_session], or by
+/// [SsoSession::new] from any `[sso-session]` section. As with [crate::Profile], invalid values are reported as
+/// an [Error::InvalidSetting] and writes go through [Section::set].
+///
+/// The `sso_registration_scopes` setting is a comma-separated list, which can be changed one scope at a time:
+///
+/// ```
+/// # use aws_config_mod::AwsConfigFile;
+/// let mut config: AwsConfigFile = "[sso-session my-sso]\nsso_registration_scopes = sso:account:access\n"
+///     .parse()
+///     .unwrap();
+///
+/// let mut sso_session = config.sso_session_mut(&"my-sso".parse().unwrap()).unwrap();
+/// sso_session.add_sso_registration_scope("codewhisperer:completions").unwrap();
+///
+/// assert_eq!(
+///     config.to_string(),
+///     "[sso-session my-sso]\nsso_registration_scopes = sso:account:access,codewhisperer:completions\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SsoSession<S> {
+    /// The `[sso-session]` section this is a view of
+    pub(crate) section: S,
+}
+
+impl<S> SsoSession<S>
+where
+    S: Deref<Target = Section<ConfigHeader>>,
+{
+    /// The name of the setting for the URL of the AWS access portal
+    const SSO_START_URL: &'static str = "sso_start_url";
+
+    /// The name of the setting for the region of the AWS access portal
+    const SSO_REGION: &'static str = "sso_region";
+
+    /// The name of the setting for the scopes which are authorized
+    const SSO_REGISTRATION_SCOPES: &'static str = "sso_registration_scopes";
+
+    /// Create a view of the given section, or return [None] if it isn't an `[sso-session]` section
+    pub fn new(section: S) -> Option<Self> {
+        (*section.get_type() == SectionType::SsoSession).then_some(Self { section })
+    }
+
+    /// The `[sso-session]` section this is a view of
+    pub fn section(&self) -> &Section<ConfigHeader> {
+        &self.section
+    }
+
+    /// The name of the session, which profiles refer to through their `sso_session` setting
+    pub fn name(&self) -> Option<&SectionName> {
+        self.section.get_name()
+    }
+
+    /// The URL of the AWS access portal, from the `sso_start_url` setting
+    pub fn sso_start_url(&self) -> Option<&Value> {
+        self.section.single_value(Self::SSO_START_URL)
+    }
+
+    /// The region of the AWS access portal, from the `sso_region` setting
+    pub fn sso_region(&self) -> Result<Option<Region>, Error> {
+        self.section.typed_value(Self::SSO_REGION)
+    }
+
+    /// The scopes which are authorized, from the comma-separated `sso_registration_scopes` setting, in the
+    /// order they are written. Empty if the setting is missing.
+    pub fn sso_registration_scopes(&self) -> Vec<&str> {
+        self.section
+            .single_value(Self::SSO_REGISTRATION_SCOPES)
+            .map(|scopes| {
+                scopes
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|scope| !scope.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl<S> SsoSession<S>
+where
+    S: DerefMut<Target = Section<ConfigHeader>>,
+{
+    /// The `[sso-session]` section this is a view of, which can be changed directly, such as to remove a setting
+    pub fn section_mut(&mut self) -> &mut Section<ConfigHeader> {
+        &mut self.section
+    }
+
+    /// Set the `sso_start_url` setting
+    pub fn set_sso_start_url(&mut self, sso_start_url: Value) {
+        self.section.set_str(Self::SSO_START_URL, &sso_start_url)
+    }
+
+    /// Set the `sso_region` setting
+    pub fn set_sso_region(&mut self, sso_region: Region) {
+        self.section.set_str(Self::SSO_REGION, &sso_region)
+    }
+
+    /// Replace the scopes in the `sso_registration_scopes` setting, removing the setting if there are none.
+    /// Fails with [Error::InvalidValue] without changing anything if a scope is empty or contains a comma or
+    /// whitespace.
+    pub fn set_sso_registration_scopes(
+        &mut self,
+        scopes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<(), Error> {
+        let scopes = scopes
+            .into_iter()
+            .map(|scope| Self::checked_scope(scope.as_ref()).map(str::to_string))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.write_scopes(&scopes);
+        Ok(())
+    }
+
+    /// Add a scope to the end of the `sso_registration_scopes` setting, creating the setting if needed. Returns
+    /// whether it was added, which it isn't if it is already there. Fails with [Error::InvalidValue] if the scope
+    /// is empty or contains a comma or whitespace.
+    pub fn add_sso_registration_scope(&mut self, scope: &str) -> Result<bool, Error> {
+        let scope = Self::checked_scope(scope)?;
+        let mut scopes = self.scopes();
+        if scopes.iter().any(|existing| existing == scope) {
+            return Ok(false);
+        }
+
+        scopes.push(scope.to_string());
+        self.write_scopes(&scopes);
+        Ok(true)
+    }
+
+    /// Remove a scope from the `sso_registration_scopes` setting, removing the setting if it was the last one.
+    /// Returns whether the scope was there.
+    pub fn remove_sso_registration_scope(&mut self, scope: &str) -> bool {
+        let mut scopes = self.scopes();
+        let count = scopes.len();
+        scopes.retain(|existing| existing != scope);
+        if scopes.len() == count {
+            return false;
+        }
+
+        self.write_scopes(&scopes);
+        true
+    }
+
+    /// The current scopes as owned strings, so that they can be changed and written back
+    fn scopes(&self) -> Vec<String> {
+        self.sso_registration_scopes()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Write the scopes to the `sso_registration_scopes` setting, keeping the separator already used in the file,
+    /// or remove the setting if there are none
+    fn write_scopes(&mut self, scopes: &[String]) {
+        if scopes.is_empty() {
+            self.section
+                .remove_setting(&SettingName(Self::SSO_REGISTRATION_SCOPES.to_string()));
+            return;
+        }
+
+        let separator = match self.section.single_value(Self::SSO_REGISTRATION_SCOPES) {
+            Some(existing) if existing.contains(", ") => ", ",
+            _ => ",",
+        };
+
+        self.section
+            .set_str(Self::SSO_REGISTRATION_SCOPES, &scopes.join(separator))
+    }
+
+    /// Check that a scope can be written to the comma-separated list
+    fn checked_scope(scope: &str) -> Result<&str, Error> {
+        match !scope.is_empty() && !scope.contains(|c: char| c == ',' || c.is_whitespace()) {
+            true => Ok(scope),
+            false => Err(Error::InvalidValue {
+                value: scope.to_string(),
+                expected: "a scope such as 'sso:account:access'",
+            }),
+        }
+    }
+}
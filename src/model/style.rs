@@ -0,0 +1,118 @@
+//! Contains items related to the formatting of a file. The formatting is detected when a file is parsed, so
+//! that anything added to the file afterwards looks like what was already there.
+
+use super::{equal::Equal, indent::Indent, LineEnding, Section, ValueType};
+
+/// The formatting used for any lines added to a file. When a file is parsed, each part of it is detected
+/// from the lines already in the file, and falls back to the [Default] if the file has no such lines.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Style {
+    /// The character sequence that ends each line
+    pub(crate) line_ending: LineEnding,
+
+    /// The '=' sign between the name and the value of a setting, along with the spaces or tabs around it
+    pub(crate) equal: Equal,
+
+    /// The number of blank lines between two sections
+    pub(crate) blank_lines_between_sections: usize,
+
+    /// The indentation of nested settings, which may consist of spaces or tabs
+    pub(crate) nested_indent: Indent,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            line_ending: LineEnding::default(),
+            equal: Equal::default(),
+            blank_lines_between_sections: 1,
+            nested_indent: Indent::spaces(2),
+        }
+    }
+}
+
+impl Style {
+    /// Detect the style of a file from its `input` and the `sections` it was parsed into. Each part of the
+    /// style is the one used most often in the file. In the event of a tie, the one which appears first wins.
+    pub(crate) fn detect<T>(input: &str, sections: &[Section<T>]) -> Self {
+        let default = Self::default();
+
+        let settings = sections.iter().flat_map(|section| section.settings.iter());
+        let nested_settings = settings.clone().flat_map(|setting| match &setting.value {
+            ValueType::Single(_) => [].iter(),
+            ValueType::Nested(nested) => nested.iter(),
+        });
+
+        let equal = settings
+            .filter(|setting| matches!(setting.value, ValueType::Single(_)))
+            .map(|setting| &setting.equal)
+            .chain(nested_settings.clone().map(|nested| &nested.equal));
+
+        let blank_lines_between_sections = sections
+            .iter()
+            .skip(1)
+            .map(|section| section.leading_whitespace.blank_lines());
+
+        Self {
+            line_ending: LineEnding::detect(input),
+            equal: most_common(equal).cloned().unwrap_or(default.equal),
+            blank_lines_between_sections: most_common(blank_lines_between_sections)
+                .unwrap_or(default.blank_lines_between_sections),
+            nested_indent: most_common(nested_settings.map(|nested| &nested.leading_spaces))
+                .cloned()
+                .unwrap_or(default.nested_indent),
+        }
+    }
+}
+
+/// Find the item which appears most often. In the event of a tie, the one which appears first is returned.
+fn most_common<T: PartialEq>(items: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: Vec<(T, usize)> = vec![];
+
+    for item in items {
+        match counts.iter_mut().find(|(existing, _)| *existing == item) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((item, 1)),
+        }
+    }
+
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(item, _)| item)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Style;
+    use crate::{
+        model::{equal::Equal, header::ConfigHeader, indent::Indent, section::parse_sections},
+        LineEnding,
+    };
+
+    #[test]
+    fn detects_the_most_common_formatting() {
+        let input = "[default]\r\nregion=us-east-1\r\ns3 =\r\n\tmax_queue_size=1000\r\n\r\n\r\n[profile A]\r\noutput = json\r\n\r\n\r\n[profile B]\r\n";
+        let (_, (_, _, sections, _)) =
+            parse_sections::<ConfigHeader>(input, Err).expect("Should be valid");
+
+        let style = Style::detect(input, &sections);
+
+        assert_eq!(style.line_ending, LineEnding::CrLf);
+        assert_eq!(style.equal, Equal::padded(0));
+        assert_eq!(style.blank_lines_between_sections, 2);
+        assert_eq!(style.nested_indent, "\t");
+    }
+
+    #[test]
+    fn falls_back_to_the_default_formatting() {
+        let (_, (_, _, sections, _)) =
+            parse_sections::<ConfigHeader>("[default]\n", Err).expect("Should be valid");
+
+        let style = Style::detect("[default]\n", &sections);
+
+        assert_eq!(style, Style::default());
+        assert_eq!(style.nested_indent, Indent::spaces(2));
+    }
+}
@@ -1,10 +1,20 @@
 //! Contains items related to parsing and stringifying setting values
 
-use crate::lexer::{Parsable, ParserOutput};
-use nom::{character::complete::none_of, combinator::recognize, multi::many1_count};
-use std::{fmt::Display, ops::Deref};
+use crate::error::Expected;
+use crate::lexer::{parse_complete, Parsable, ParserOutput};
+use nom::{
+    character::complete::{none_of, space1},
+    combinator::recognize,
+    error::context,
+    multi::{many0_count, separated_list1},
+    sequence::pair,
+};
+use std::{fmt::Display, ops::Deref, str::FromStr};
 
 /// Represents the value of a setting. In other words, whatever follows the = sign in a configuration setting.
+///
+/// A value runs to the end of the line and may contain spaces, such as `less -R`. As with botocore, the
+/// whitespace surrounding the value is not part of it, and a `#` preceded by whitespace starts a comment.
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Hash)]
 pub struct Value(String);
 
@@ -38,12 +48,28 @@ impl<'a> Parsable<'a> for Value {
     type Output = Self;
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
-        let (input, val) = recognize(many1_count(none_of("#\n\t \r")))(input)?;
+        let (input, val) = context(
+            Expected::Value.label(),
+            recognize(separated_list1(space1, word)),
+        )(input)?;
 
         Ok((input, Value(val.to_string())))
     }
 }
 
+/// A helper parser that matches a run of non-whitespace characters which does not start a comment
+fn word(input: &str) -> ParserOutput<'_, &str> {
+    recognize(pair(none_of("#\n\t \r"), many0_count(none_of("\n\t \r"))))(input)
+}
+
+impl FromStr for Value {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse_complete::<Self>(s)?)
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -88,4 +114,60 @@ mod test {
         assert_eq!(val.0, value);
         assert_eq!(&val.to_string(), value)
     }
+
+    #[test]
+    fn parses_value_with_spaces() {
+        let value = "/usr/local/bin/helper --profile x";
+
+        let (input, val) = Value::parse(value).expect("Should be valid");
+
+        assert!(input.is_empty());
+        assert_eq!(val.0, value);
+        assert_eq!(&val.to_string(), value)
+    }
+
+    #[test]
+    fn leaves_trailing_whitespace_and_comment() {
+        let (input, val) = Value::parse("less -R   # pager\n").expect("Should be valid");
+
+        assert_eq!(input, "   # pager\n");
+        assert_eq!(val.0, "less -R");
+    }
+
+    #[test]
+    fn keeps_hash_inside_a_word() {
+        let (input, val) = Value::parse("https://example.com/#anchor").expect("Should be valid");
+
+        assert!(input.is_empty());
+        assert_eq!(val.0, "https://example.com/#anchor");
+    }
+
+    #[test]
+    fn keeps_inner_spacing() {
+        let (input, val) = Value::parse("a  b\tc\r\n").expect("Should be valid");
+
+        assert_eq!(input, "\r\n");
+        assert_eq!(val.0, "a  b\tc");
+    }
+
+    #[test]
+    fn comment_is_not_a_value() {
+        Value::parse("# comment").expect_err("A comment is not a value");
+    }
+
+    #[test]
+    fn parsing_a_whole_value_rejects_what_would_not_read_back() {
+        for input in [
+            "",
+            "x\n[profile evil]",
+            "less # comment",
+            " leading",
+            "trailing ",
+        ] {
+            assert!(input.parse::<Value>().is_err(), "{input:?}");
+        }
+
+        let value = "less -R".parse::<Value>().expect("Should be valid");
+        assert_eq!(value.0, "less -R");
+    }
 }
@@ -1,7 +1,7 @@
 //! Contains items related to distinguishing whether a value for a setting is a list of nested settings, or whether it is
 //! a single value
 
-use super::{nested_setting::NestedSetting, nested_settings::NestedSettings, Value};
+use super::{nested_settings::NestedSettings, Value};
 use crate::lexer::Parsable;
 use nom::{branch::alt, combinator::map};
 use std::fmt::Display;
@@ -21,17 +21,7 @@ impl Display for ValueType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ValueType::Single(single) => write!(f, "{single}"),
-            ValueType::Nested(NestedSettings {
-                leading_whitespace,
-                nested_settings,
-            }) => write!(
-                f,
-                "{leading_whitespace}{}",
-                nested_settings
-                    .iter()
-                    .map(NestedSetting::to_string)
-                    .collect::<String>()
-            ),
+            ValueType::Nested(nested) => write!(f, "{nested}"),
         }
     }
 }
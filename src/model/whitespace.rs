@@ -1,12 +1,14 @@
 //! Contains items related to parsing and stringifying whitespace. Whitespace includes tabs, spaces, newlines, and comments.
 
+use super::LineEnding;
 use crate::lexer::{hash, newline, Parsable, ParserOutput};
 use nom::{
     branch::alt,
-    character::complete::{crlf, not_line_ending, space0},
-    combinator::{opt, recognize},
+    character::complete::{not_line_ending, space0, space1},
+    combinator::{eof, opt, recognize},
     error::VerboseError,
-    multi::separated_list0,
+    multi::{many0_count, separated_list0},
+    sequence::{pair, terminated, tuple},
     IResult, Parser,
 };
 use std::{fmt::Display, ops::Deref};
@@ -16,12 +18,271 @@ use std::{fmt::Display, ops::Deref};
 pub(crate) struct Whitespace(pub(crate) String);
 
 impl Whitespace {
-    /// Generate a new [Whitespace] instance that only contains a single newline character
-    pub fn newline() -> Self {
-        Whitespace(String::from("\n")) // TODO: need to detect newlines from file
+    /// Generate a new [Whitespace] instance that only contains a single line ending
+    pub fn newline(line_ending: LineEnding) -> Self {
+        Whitespace(String::from(line_ending.as_str()))
+    }
+
+    /// Parse the remainder of a line that holds a header or a setting: optional spaces, an optional comment and
+    /// the line ending, which may only be missing on the last line of the input.
+    pub(crate) fn parse_line_end(input: &str) -> ParserOutput<'_, Self> {
+        let (next, whitespace) =
+            recognize(tuple((space0, opt(comment), alt((newline, eof))))).parse(input)?;
+
+        Ok((next, Whitespace(whitespace.to_string())))
+    }
+
+    /// Parse any number of whole lines which are blank or only hold a comment, along with their line endings.
+    /// Unlike [Whitespace::parse], this never consumes the indentation of a line that has content.
+    pub(crate) fn parse_lines(input: &str) -> ParserOutput<'_, Self> {
+        let (next, whitespace) = recognize(pair(
+            many0_count(recognize(tuple((space0, opt(comment), newline)))),
+            opt(terminated(pair(space0, opt(comment)), eof)),
+        ))
+        .parse(input)?;
+
+        Ok((next, Whitespace(whitespace.to_string())))
+    }
+
+    /// Parse any number of indented comment lines, which continue a block of [crate::NestedSetting]s.
+    pub(crate) fn parse_indented_comments(input: &str) -> ParserOutput<'_, Self> {
+        let (next, whitespace) =
+            recognize(many0_count(tuple((space1, comment, alt((newline, eof)))))).parse(input)?;
+
+        Ok((next, Whitespace(whitespace.to_string())))
+    }
+
+    /// Add a line ending unless this whitespace already ends with one, so that anything written after it
+    /// starts on a line of its own.
+    pub(crate) fn end_line(&mut self, line_ending: LineEnding) {
+        if !self.0.ends_with('\n') {
+            self.0.push_str(line_ending.as_str())
+        }
+    }
+
+    /// Split off the blank lines at the start, which separate whatever owns this whitespace from the
+    /// content above it. Returns the separator and leaves the remaining lines in place.
+    pub(crate) fn take_separator(&mut self) -> Self {
+        let length = self
+            .0
+            .split_inclusive('\n')
+            .take_while(|line| is_blank(line) && line.ends_with('\n'))
+            .map(str::len)
+            .sum();
+        let rest = self.0.split_off(length);
+
+        Whitespace(std::mem::replace(&mut self.0, rest))
+    }
+
+    /// Make sure this whitespace starts with at least as many blank lines as `separator` holds, so that
+    /// the separation provided by a removed neighbour is not lost.
+    pub(crate) fn merge_separator(&mut self, separator: Whitespace) {
+        let existing = self.blank_lines();
+        let missing = separator
+            .0
+            .split_inclusive('\n')
+            .skip(existing)
+            .collect::<String>();
+
+        self.0.insert_str(0, &missing);
+    }
+
+    /// Split this whitespace in two where the lines stop belonging to the content above and start belonging
+    /// to the content below. The second half is the run of comment lines directly above the next piece of
+    /// content together with the blank lines above those comments.
+    pub(crate) fn split_attached(mut self) -> (Self, Self) {
+        let lines = self.0.split_inclusive('\n').collect::<Vec<_>>();
+        let comments = lines
+            .iter()
+            .rev()
+            .take_while(|line| is_comment(line))
+            .count();
+        let blanks = lines
+            .iter()
+            .rev()
+            .skip(comments)
+            .take_while(|line| is_blank(line))
+            .count();
+        let length = lines
+            .iter()
+            .rev()
+            .take(comments + blanks)
+            .map(|line| line.len())
+            .sum::<usize>();
+        let attached = self.0.split_off(self.0.len() - length);
+
+        (self, Whitespace(attached))
+    }
+
+    /// The number of blank lines at the start, which separate whatever owns this whitespace from the
+    /// content above it
+    pub(crate) fn blank_lines(&self) -> usize {
+        self.0
+            .split_inclusive('\n')
+            .take_while(|line| is_blank(line) && line.ends_with('\n'))
+            .count()
+    }
+
+    /// Replace the blank lines at the start with the given number of empty lines
+    pub(crate) fn set_blank_lines(&mut self, count: usize, line_ending: LineEnding) {
+        self.take_separator();
+        self.0.insert_str(0, &line_ending.as_str().repeat(count));
+    }
+
+    /// The spaces and tabs after the last line break, which indent the content that follows
+    pub(crate) fn indentation(&self) -> &str {
+        let last_line = self.0.rsplit('\n').next().unwrap_or_default();
+
+        match is_blank(last_line) {
+            true => last_line,
+            false => "",
+        }
+    }
+
+    /// The text of each comment line, without the comment marker and the space after it
+    pub(crate) fn comments(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .split_inclusive('\n')
+            .filter(|line| is_comment(line))
+            .map(comment_text)
+    }
+
+    /// Replace the comment lines, and any blank lines after the blank lines at the start, with the given
+    /// comments. Each comment is written on its own line after `indent`, and a comment which spans several
+    /// lines becomes several comment lines. The blank lines at the start and the indentation at the end are
+    /// kept.
+    pub(crate) fn set_comments(
+        &mut self,
+        comments: impl IntoIterator<Item = impl AsRef<str>>,
+        indent: &str,
+        line_ending: LineEnding,
+    ) {
+        let Whitespace(mut whitespace) = self.take_separator();
+
+        for comment in comments {
+            for line in comment.as_ref().split('\n') {
+                whitespace.push_str(indent);
+                whitespace.push_str(&format_comment(line.trim_end_matches('\r')));
+                whitespace.push_str(line_ending.as_str());
+            }
+        }
+
+        whitespace.push_str(self.indentation());
+        self.0 = whitespace;
+    }
+
+    /// The text of the comment on the rest of a line, without the comment marker and the space after it
+    pub(crate) fn inline_comment(&self) -> Option<&str> {
+        inline_comment(&self.0)
+    }
+
+    /// Replace the comment on the rest of a line, keeping the spaces in front of an existing comment and the
+    /// line ending. Line breaks within the comment are replaced by spaces, as it has to fit on one line.
+    /// Without a comment, only the line ending is kept.
+    pub(crate) fn set_inline_comment(&mut self, comment: Option<&str>) {
+        let content = self.0.trim_end_matches(['\r', '\n']);
+        let line_ending = &self.0[content.len()..];
+        let padding = &content[..content.len() - content.trim_start_matches([' ', '\t']).len()];
+
+        self.0 = match comment {
+            Some(comment) => {
+                let padding = if padding.is_empty() { " " } else { padding };
+                let comment = comment.lines().collect::<Vec<_>>().join(" ");
+
+                format!("{padding}{}{line_ending}", format_comment(&comment))
+            }
+            None => line_ending.to_string(),
+        };
+    }
+
+    /// Format the rest of a line: no spaces unless there is a comment, which is preceded by `comment_spacing`
+    /// spaces. If the line was terminated, it is terminated by the given line ending. The text of the comment
+    /// is kept as it is.
+    pub(crate) fn format_line_end(&mut self, comment_spacing: usize, line_ending: LineEnding) {
+        let content = self.0.trim_matches([' ', '\t', '\r', '\n']);
+        let mut whitespace = String::new();
+
+        if !content.is_empty() {
+            whitespace.push_str(&" ".repeat(comment_spacing));
+            whitespace.push_str(content);
+        }
+        if self.0.ends_with('\n') {
+            whitespace.push_str(line_ending.as_str());
+        }
+
+        self.0 = whitespace;
+    }
+
+    /// Format whole lines: the given number of blank lines at the start, or as many as there are already if
+    /// [None], and no other blank lines. Comment lines are indented by `indent`, and every line is terminated
+    /// by the given line ending. The text of comments is kept as it is, and the indentation at the end is
+    /// removed.
+    pub(crate) fn format_lines(
+        &mut self,
+        blank_lines: Option<usize>,
+        indent: &str,
+        line_ending: LineEnding,
+    ) {
+        let blank_lines = blank_lines.unwrap_or_else(|| self.blank_lines());
+        let mut whitespace = line_ending.as_str().repeat(blank_lines);
+
+        for line in self.0.split_inclusive('\n').filter(|line| !is_blank(line)) {
+            whitespace.push_str(indent);
+            whitespace.push_str(line.trim_matches([' ', '\t', '\r', '\n']));
+            whitespace.push_str(line_ending.as_str());
+        }
+
+        self.0 = whitespace;
+    }
+
+    /// Whether this whitespace ends with a line break
+    pub(crate) fn ends_with_line_break(&self) -> bool {
+        self.0.ends_with('\n')
+    }
+
+    /// Remove the line ending at the end, if there is one
+    pub(crate) fn remove_line_break(&mut self) {
+        let length = self.0.trim_end_matches(['\r', '\n']).len();
+
+        self.0.truncate(length);
+    }
+}
+
+/// The text of the comment in the whitespace at the end of a line, without the comment marker and the space
+/// after it
+pub(crate) fn inline_comment(line_end: &str) -> Option<&str> {
+    is_comment(line_end).then(|| comment_text(line_end))
+}
+
+/// The text of a comment line, without its indentation, the comment marker, the space after the marker and
+/// the line ending
+fn comment_text(line: &str) -> &str {
+    let comment = line
+        .trim_start_matches([' ', '\t'])
+        .trim_end_matches(['\r', '\n']);
+    let text = comment.strip_prefix('#').unwrap_or(comment);
+
+    text.strip_prefix(' ').unwrap_or(text)
+}
+
+/// Turn the text of a comment into a comment, without a line ending
+fn format_comment(text: &str) -> String {
+    match text.is_empty() {
+        true => String::from("#"),
+        false => format!("# {text}"),
     }
 }
 
+/// Whether a line contains nothing but spaces, tabs and its line ending
+fn is_blank(line: &str) -> bool {
+    line.trim_matches([' ', '\t', '\r', '\n']).is_empty()
+}
+
+/// Whether a line holds nothing but a comment
+fn is_comment(line: &str) -> bool {
+    line.trim_start_matches([' ', '\t']).starts_with('#')
+}
+
 impl Deref for Whitespace {
     type Target = str;
 
@@ -47,7 +308,7 @@ impl<'a> Parsable<'a> for Whitespace {
 
     fn parse(input: &'a str) -> ParserOutput<'a, Self::Output> {
         let (next, whitespace) =
-            recognize(separated_list0(line_end, space0.and(opt(comment)))).parse(input)?;
+            recognize(separated_list0(newline, space0.and(opt(comment)))).parse(input)?;
 
         Ok((next, Whitespace(whitespace.to_string())))
     }
@@ -58,15 +319,10 @@ fn comment(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
     recognize(hash.and(not_line_ending)).parse(input)
 }
 
-/// A helper parser that matches any valid newline character
-fn line_end(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
-    alt((newline, crlf))(input)
-}
-
 #[cfg(test)]
 mod test {
     use super::Whitespace;
-    use crate::lexer::Parsable;
+    use crate::{lexer::Parsable, LineEnding};
 
     #[test]
     fn parses_a_comment_with_no_leading_space_and_newline() {
@@ -125,4 +381,80 @@ mod test {
         assert_eq!(com, *comment);
         assert_eq!(&com.to_string(), comment)
     }
+
+    #[test]
+    fn lines_stop_before_the_indentation_of_content() {
+        let input = "\n  # comment\n  region = us-east-1\n";
+
+        let (rest, lines) = Whitespace::parse_lines(input).expect("Should be ok");
+
+        assert_eq!(rest, "  region = us-east-1\n");
+        assert_eq!(lines, *"\n  # comment\n");
+    }
+
+    #[test]
+    fn comments_directly_above_content_are_attached_to_it() {
+        let whitespace = Whitespace("# about A\n\n\n# about B\n".to_string());
+
+        let (detached, attached) = whitespace.split_attached();
+
+        assert_eq!(detached, *"# about A\n");
+        assert_eq!(attached, *"\n\n# about B\n");
+    }
+
+    #[test]
+    fn reads_and_replaces_comment_lines() {
+        let mut whitespace = Whitespace("\n  # owner: platform-team\n  #\n\n  ".to_string());
+
+        assert_eq!(
+            whitespace.comments().collect::<Vec<_>>(),
+            ["owner: platform-team", ""]
+        );
+        assert_eq!(whitespace.blank_lines(), 1);
+        assert_eq!(whitespace.indentation(), "  ");
+
+        whitespace.set_comments(["owner: data-team", "two\r\nlines"], "  ", LineEnding::CrLf);
+
+        assert_eq!(
+            whitespace,
+            *"\n  # owner: data-team\r\n  # two\r\n  # lines\r\n  "
+        );
+    }
+
+    #[test]
+    fn replaces_the_blank_lines_at_the_start() {
+        let mut whitespace = Whitespace("\n\n\n# comment\n".to_string());
+
+        whitespace.set_blank_lines(1, LineEnding::Lf);
+
+        assert_eq!(whitespace, *"\n# comment\n");
+    }
+
+    #[test]
+    fn replaces_an_inline_comment_and_keeps_its_padding() {
+        let mut whitespace = Whitespace("   #old\r\n".to_string());
+        assert_eq!(whitespace.inline_comment(), Some("old"));
+
+        whitespace.set_inline_comment(Some("new"));
+        assert_eq!(whitespace, *"   # new\r\n");
+
+        whitespace.set_inline_comment(None);
+        assert_eq!(whitespace, *"\r\n");
+        assert_eq!(whitespace.inline_comment(), None);
+
+        whitespace.set_inline_comment(Some("added"));
+        assert_eq!(whitespace, *" # added\r\n");
+    }
+
+    #[test]
+    fn separators_are_merged_without_adding_up() {
+        let mut removed = Whitespace("\n\n# removed\n".to_string());
+        let separator = removed.take_separator();
+        assert_eq!(removed, *"# removed\n");
+
+        let mut next = Whitespace("\n# next\n".to_string());
+        next.merge_separator(separator);
+
+        assert_eq!(next, *"\n\n# next\n");
+    }
 }